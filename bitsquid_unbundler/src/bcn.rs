@@ -0,0 +1,645 @@
+use crate::texture::TextureFormat;
+use crate::unbundler::UnbundlerError;
+
+type Block = [[u8; 4]; 16]; //the rgba pixels of a 4x4 block in rows.
+
+//decodes one surface of a texture into rgba8 pixels, width * height * 4 bytes.
+pub fn decode(
+    format: TextureFormat,
+    data: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, UnbundlerError> {
+    let (width, height) = (width as usize, height as usize);
+    let size = format
+        .surface_size(width as u32, height as u32, 1)
+        .ok_or_else(|| {
+            UnbundlerError::Texture(format!("a {}x{} surface is too large", width, height))
+        })?;
+    let data = data.get(..size).ok_or_else(|| {
+        UnbundlerError::Texture(format!(
+            "the surface has {} bytes but needs {}",
+            data.len(),
+            size
+        ))
+    })?;
+
+    let mut pixels = vec![0u8; width * height * 4];
+    let decode_block: fn(&[u8], &mut Block) = match format {
+        TextureFormat::Bc1 => |block, out| decode_bc1(block, out, true),
+        TextureFormat::Bc2 => decode_bc2,
+        TextureFormat::Bc3 => decode_bc3,
+        TextureFormat::Bc4 => decode_bc4,
+        TextureFormat::Bc5 => decode_bc5,
+        TextureFormat::Bc7 => decode_bc7,
+        TextureFormat::Bc6h => {
+            return Err(UnbundlerError::Texture(
+                "BC6H textures cannot be decoded".to_string(),
+            ))
+        }
+        TextureFormat::Rgba8 | TextureFormat::Bgra8 | TextureFormat::R8 => {
+            for (pixel, out) in data
+                .chunks_exact(format.bytes_per_pixel())
+                .zip(pixels.chunks_exact_mut(4))
+            {
+                out.copy_from_slice(&match format {
+                    TextureFormat::Rgba8 => [pixel[0], pixel[1], pixel[2], pixel[3]],
+                    TextureFormat::Bgra8 => [pixel[2], pixel[1], pixel[0], pixel[3]],
+                    _ => [pixel[0], pixel[0], pixel[0], 255],
+                });
+            }
+            return Ok(pixels);
+        }
+    };
+
+    let block_size = format.block_size().unwrap();
+    let blocks_wide = width.div_ceil(4);
+    let mut block = [[0u8; 4]; 16];
+    for (index, bytes) in data.chunks_exact(block_size).enumerate() {
+        decode_block(bytes, &mut block);
+        let (block_x, block_y) = (index % blocks_wide * 4, index / blocks_wide * 4);
+
+        //blocks on the right and bottom edges can hang over the surface.
+        for (pixel_index, pixel) in block.iter().enumerate() {
+            let (x, y) = (block_x + pixel_index % 4, block_y + pixel_index / 4);
+            if x < width && y < height {
+                let offset = (y * width + x) * 4;
+                pixels[offset..offset + 4].copy_from_slice(pixel);
+            }
+        }
+    }
+
+    Ok(pixels)
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let (r, g, b) = ((color >> 11) & 31, (color >> 5) & 63, color & 31);
+    [
+        (r << 3 | r >> 2) as u8,
+        (g << 2 | g >> 4) as u8,
+        (b << 3 | b >> 2) as u8,
+        255,
+    ]
+}
+
+//bc2 and bc3 always use four colors. bc1 has three and transparent black when c0 <= c1.
+fn bc1_palette(c0: u16, c1: u16, punch_through: bool) -> [[u8; 4]; 4] {
+    let (e0, e1) = (rgb565(c0), rgb565(c1));
+    let mix = |a: u8, b: u8, wa: u16, wb: u16| ((a as u16 * wa + b as u16 * wb) / (wa + wb)) as u8;
+
+    let mut colors = [e0, e1, [0; 4], [0; 4]];
+    if c0 > c1 || !punch_through {
+        for channel in 0..3 {
+            colors[2][channel] = mix(e0[channel], e1[channel], 2, 1);
+            colors[3][channel] = mix(e0[channel], e1[channel], 1, 2);
+        }
+        colors[2][3] = 255;
+        colors[3][3] = 255;
+    } else {
+        for channel in 0..3 {
+            colors[2][channel] = mix(e0[channel], e1[channel], 1, 1);
+        }
+        colors[2][3] = 255;
+    }
+    colors
+}
+
+fn decode_bc1(bytes: &[u8], out: &mut Block, punch_through: bool) {
+    let colors = bc1_palette(u16_at(bytes, 0), u16_at(bytes, 2), punch_through);
+    let indices = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+    for (pixel, out) in out.iter_mut().enumerate() {
+        *out = colors[(indices >> (pixel * 2) & 3) as usize];
+    }
+}
+
+fn decode_bc2(bytes: &[u8], out: &mut Block) {
+    decode_bc1(&bytes[8..16], out, false);
+    let alpha = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+    for (pixel, out) in out.iter_mut().enumerate() {
+        out[3] = (alpha >> (pixel * 4) & 15) as u8 * 17;
+    }
+}
+
+fn decode_bc3(bytes: &[u8], out: &mut Block) {
+    decode_bc1(&bytes[8..16], out, false);
+    for (pixel, value) in decode_bc4_channel(&bytes[0..8]).into_iter().enumerate() {
+        out[pixel][3] = value;
+    }
+}
+
+fn decode_bc4(bytes: &[u8], out: &mut Block) {
+    for (pixel, value) in decode_bc4_channel(bytes).into_iter().enumerate() {
+        out[pixel] = [value, value, value, 255];
+    }
+}
+
+//two channel normal maps: red and green are stored, blue is left at 0.
+fn decode_bc5(bytes: &[u8], out: &mut Block) {
+    let red = decode_bc4_channel(&bytes[0..8]);
+    let green = decode_bc4_channel(&bytes[8..16]);
+    for pixel in 0..16 {
+        out[pixel] = [red[pixel], green[pixel], 0, 255];
+    }
+}
+
+//two endpoints and eight values between them, or six and 0 and 255 when e0 <= e1.
+fn bc4_palette(e0: u8, e1: u8) -> [u8; 8] {
+    let (e0, e1) = (e0 as u32, e1 as u32);
+    let mut values = [e0, e1, 0, 0, 0, 0, 0, 255];
+    if e0 > e1 {
+        for i in 1..7 {
+            values[i + 1] = ((7 - i as u32) * e0 + i as u32 * e1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            values[i + 1] = ((5 - i as u32) * e0 + i as u32 * e1) / 5;
+        }
+    }
+    values.map(|value| value as u8)
+}
+
+fn decode_bc4_channel(bytes: &[u8]) -> [u8; 16] {
+    let values = bc4_palette(bytes[0], bytes[1]);
+    let mut indices = [0u8; 8];
+    indices[..6].copy_from_slice(&bytes[2..8]);
+    let indices = u64::from_le_bytes(indices);
+
+    let mut channel = [0u8; 16];
+    for (pixel, value) in channel.iter_mut().enumerate() {
+        *value = values[(indices >> (pixel * 3) & 7) as usize];
+    }
+    channel
+}
+
+//(subsets, partition bits, rotation bits, index selection bits, color bits, alpha bits,
+//endpoint p-bits, shared p-bits, index bits, secondary index bits) of bc7 modes 0 to 7.
+const BC7_MODES: [[u32; 10]; 8] = [
+    [3, 4, 0, 0, 4, 0, 1, 0, 3, 0],
+    [2, 6, 0, 0, 6, 0, 0, 1, 3, 0],
+    [3, 6, 0, 0, 5, 0, 0, 0, 2, 0],
+    [2, 6, 0, 0, 7, 0, 1, 0, 2, 0],
+    [1, 0, 2, 1, 5, 6, 0, 0, 2, 3],
+    [1, 0, 2, 0, 7, 8, 0, 0, 2, 2],
+    [1, 0, 0, 0, 7, 7, 1, 0, 4, 0],
+    [2, 6, 0, 0, 5, 5, 1, 0, 2, 0],
+];
+
+//bit n is the subset of pixel n.
+const BC7_PARTITIONS_2: [u16; 64] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80, 0xc800, 0xffec, 0xfe80, 0xe800,
+    0xffe8, 0xff00, 0xfff0, 0xf000, 0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce,
+    0x088c, 0x3110, 0x6666, 0x366c, 0x17e8, 0x0ff0, 0x718e, 0x399c, 0xaaaa, 0xf0f0, 0x5a5a, 0x33cc,
+    0x3c3c, 0x55aa, 0x9696, 0xa55a, 0x73ce, 0x13c8, 0x324c, 0x3bdc, 0x6996, 0xc33c, 0x9966, 0x0660,
+    0x0272, 0x04e4, 0x4e40, 0x2720, 0xc936, 0x936c, 0x39c6, 0x639c, 0x9336, 0x9cc6, 0x817e, 0xe718,
+    0xccf0, 0x0fcc, 0x7744, 0xee22,
+];
+
+//two bits per pixel, pixel n at bit 2n.
+const BC7_PARTITIONS_3: [u32; 64] = [
+    0xaa685050, 0x6a5a5040, 0x5a5a4200, 0x5450a0a8, 0xa5a50000, 0xa0a05050, 0x5555a0a0, 0x5a5a5050,
+    0xaa550000, 0xaa555500, 0xaaaa5500, 0x90909090, 0x94949494, 0xa4a4a4a4, 0xa9a59450, 0x2a0a4250,
+    0xa5945040, 0x0a425054, 0xa5a5a500, 0x55a0a0a0, 0xa8a85454, 0x6a6a4040, 0xa4a45000, 0x1a1a0500,
+    0x0050a4a4, 0xaaa59090, 0x14696914, 0x69691400, 0xa08585a0, 0xaa821414, 0x50a4a450, 0x6a5a0200,
+    0xa9a58000, 0x5090a0a8, 0xa8a09050, 0x24242424, 0x00aa5500, 0x24924924, 0x24499224, 0x50a50a50,
+    0x500aa550, 0xaaaa4444, 0x66660000, 0xa5a0a5a0, 0x50a050a0, 0x69286928, 0x44aaaa44, 0x66666600,
+    0xaa444444, 0x54a854a8, 0x95809580, 0x96969600, 0xa85454a8, 0x80959580, 0xaa141414, 0x96960000,
+    0xaaaa1414, 0xa05050a0, 0xa0a5a5a0, 0x96000000, 0x40804080, 0xa9a8a9a8, 0xaaaaaa44, 0x2a4a5254,
+];
+
+const BC7_ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8,
+    2, 2, 8, 8, 2, 2, 15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2,
+    2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+const BC7_ANCHORS_3_SECOND: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5,
+    15, 15, 8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8, 5, 10, 5,
+    10, 8, 13, 15, 12, 3, 3,
+];
+
+const BC7_ANCHORS_3_THIRD: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6,
+    10, 15, 15, 10, 8, 15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15, 15, 15,
+    15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+const BC7_WEIGHTS: [&[u32]; 3] = [
+    &[0, 21, 43, 64],
+    &[0, 9, 18, 27, 37, 46, 55, 64],
+    &[0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64],
+];
+
+fn bc7_subset(subsets: u32, partition: usize, pixel: usize) -> usize {
+    match subsets {
+        2 => (BC7_PARTITIONS_2[partition] >> pixel & 1) as usize,
+        3 => (BC7_PARTITIONS_3[partition] >> (pixel * 2) & 3) as usize,
+        _ => 0,
+    }
+}
+
+//the pixel of each subset whose index is stored with one bit less.
+fn bc7_anchors(subsets: u32, partition: usize) -> [usize; 3] {
+    match subsets {
+        2 => [0, BC7_ANCHORS_2[partition] as usize, 0],
+        3 => [
+            0,
+            BC7_ANCHORS_3_SECOND[partition] as usize,
+            BC7_ANCHORS_3_THIRD[partition] as usize,
+        ],
+        _ => [0; 3],
+    }
+}
+
+fn bc7_interpolate(e0: u8, e1: u8, index: u32, index_bits: u32) -> u8 {
+    let weight = BC7_WEIGHTS[index_bits as usize - 2][index as usize];
+    (((64 - weight) * e0 as u32 + weight * e1 as u32 + 32) >> 6) as u8
+}
+
+struct BitReader {
+    bits: u128,
+    position: u32,
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> u32 {
+        let value = (self.bits >> self.position) as u32 & ((1u64 << count) - 1) as u32;
+        self.position += count;
+        value
+    }
+}
+
+fn decode_bc7(bytes: &[u8], out: &mut Block) {
+    let mut reader = BitReader {
+        bits: u128::from_le_bytes(bytes.try_into().unwrap()),
+        position: 0,
+    };
+
+    let mode = (reader.bits as u8).trailing_zeros() as usize;
+    if mode >= 8 {
+        //reserved mode, decoded as transparent black like the hardware does.
+        *out = [[0; 4]; 16];
+        return;
+    }
+    reader.position = mode as u32 + 1;
+
+    let [subsets, partition_bits, rotation_bits, selection_bits, color_bits, alpha_bits, endpoint_pbits, shared_pbits, index_bits, index_bits2] =
+        BC7_MODES[mode];
+    let partition = reader.read(partition_bits) as usize;
+    let rotation = reader.read(rotation_bits);
+    let selection = reader.read(selection_bits);
+
+    //endpoints[subset * 2 + end][channel], read channel by channel.
+    let mut endpoints = [[0u32; 4]; 6];
+    let endpoint_count = subsets as usize * 2;
+    for channel in 0..4 {
+        let bits = if channel < 3 { color_bits } else { alpha_bits };
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[channel] = if bits == 0 { 255 } else { reader.read(bits) };
+        }
+    }
+
+    let mut pbits = [0u32; 6];
+    if endpoint_pbits > 0 {
+        for pbit in pbits.iter_mut().take(endpoint_count) {
+            *pbit = reader.read(1);
+        }
+    } else if shared_pbits > 0 {
+        for subset in 0..subsets as usize {
+            let pbit = reader.read(1);
+            pbits[subset * 2] = pbit;
+            pbits[subset * 2 + 1] = pbit;
+        }
+    }
+
+    let mut colors = [[0u8; 4]; 6];
+    for (endpoint, color) in colors.iter_mut().enumerate().take(endpoint_count) {
+        for channel in 0..4 {
+            let bits = if channel < 3 { color_bits } else { alpha_bits };
+            if bits == 0 {
+                color[channel] = 255;
+                continue;
+            }
+
+            let (mut value, mut bits) = (endpoints[endpoint][channel], bits);
+            if endpoint_pbits + shared_pbits > 0 {
+                value = value << 1 | pbits[endpoint];
+                bits += 1;
+            }
+            color[channel] = (value << (8 - bits) | value >> (2 * bits - 8)) as u8;
+        }
+    }
+
+    let anchors = bc7_anchors(subsets, partition);
+    let mut read_indices = |bits: u32| {
+        let mut indices = [0u32; 16];
+        for (pixel, index) in indices.iter_mut().enumerate() {
+            let subset = bc7_subset(subsets, partition, pixel);
+            let anchor = anchors[subset] == pixel;
+            *index = reader.read(if anchor { bits - 1 } else { bits });
+        }
+        indices
+    };
+    let indices = read_indices(index_bits);
+    let indices2 = if index_bits2 > 0 {
+        read_indices(index_bits2)
+    } else {
+        indices
+    };
+
+    //with two index sets the color uses the first and alpha the second, unless the selection swaps them.
+    let (color_indices, color_index_bits, alpha_indices, alpha_index_bits) = match selection {
+        1 => (indices2, index_bits2, indices, index_bits),
+        _ if index_bits2 > 0 => (indices, index_bits, indices2, index_bits2),
+        _ => (indices, index_bits, indices, index_bits),
+    };
+
+    for (pixel, out) in out.iter_mut().enumerate() {
+        let subset = bc7_subset(subsets, partition, pixel);
+        let (e0, e1) = (colors[subset * 2], colors[subset * 2 + 1]);
+        for channel in 0..3 {
+            out[channel] = bc7_interpolate(
+                e0[channel],
+                e1[channel],
+                color_indices[pixel],
+                color_index_bits,
+            );
+        }
+        out[3] = bc7_interpolate(e0[3], e1[3], alpha_indices[pixel], alpha_index_bits);
+
+        if rotation > 0 {
+            out.swap(3, rotation as usize - 1);
+        }
+    }
+}
+
+//encodes rgba8 pixels, width * height * 4 bytes, into one surface of a texture.
+//blocks over the right and bottom edges repeat the last column and row.
+pub fn encode(
+    format: TextureFormat,
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, UnbundlerError> {
+    let (width, height) = (width as usize, height as usize);
+    if pixels.len() != width * height * 4 {
+        return Err(UnbundlerError::Texture(format!(
+            "{} bytes are not {}x{} rgba pixels",
+            pixels.len(),
+            width,
+            height
+        )));
+    }
+
+    let encode_block: fn(&Block) -> Vec<u8> = match format {
+        TextureFormat::Bc1 => encode_bc1,
+        TextureFormat::Bc2 => encode_bc2,
+        TextureFormat::Bc3 => encode_bc3,
+        TextureFormat::Bc4 => |block| encode_bc4_channel(&block.map(|pixel| pixel[0])).to_vec(),
+        TextureFormat::Bc5 => encode_bc5,
+        TextureFormat::Bc7 => encode_bc7,
+        TextureFormat::Bc6h => {
+            return Err(UnbundlerError::Texture(
+                "BC6H textures cannot be encoded".to_string(),
+            ))
+        }
+        TextureFormat::Rgba8 => return Ok(pixels.to_vec()),
+        TextureFormat::Bgra8 => {
+            return Ok(pixels
+                .chunks_exact(4)
+                .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+                .collect())
+        }
+        TextureFormat::R8 => return Ok(pixels.chunks_exact(4).map(|pixel| pixel[0]).collect()),
+    };
+
+    let mut data = Vec::with_capacity(
+        format
+            .surface_size(width as u32, height as u32, 1)
+            .unwrap_or(0),
+    );
+    let mut block = [[0u8; 4]; 16];
+    for block_y in (0..height).step_by(4) {
+        for block_x in (0..width).step_by(4) {
+            for (pixel_index, pixel) in block.iter_mut().enumerate() {
+                let x = (block_x + pixel_index % 4).min(width - 1);
+                let y = (block_y + pixel_index / 4).min(height - 1);
+                let offset = (y * width + x) * 4;
+                pixel.copy_from_slice(&pixels[offset..offset + 4]);
+            }
+            data.extend(encode_block(&block));
+        }
+    }
+
+    Ok(data)
+}
+
+//the two ends of the line through the block's colors along which they vary the most.
+fn principal_endpoints<const N: usize>(points: &[[f32; N]; 16]) -> ([f32; N], [f32; N]) {
+    let mut mean = [0f32; N];
+    for point in points.iter() {
+        for channel in 0..N {
+            mean[channel] += point[channel] / 16.0;
+        }
+    }
+
+    let mut covariance = [[0f32; N]; N];
+    for point in points.iter() {
+        for i in 0..N {
+            for j in 0..N {
+                covariance[i][j] += (point[i] - mean[i]) * (point[j] - mean[j]);
+            }
+        }
+    }
+
+    //power iteration finds the axis. it starts from the most varying channel's row,
+    //since a fixed start can be at right angles to the axis. a few steps are plenty for 16 points.
+    let widest = (0..N)
+        .max_by(|a, b| covariance[*a][*a].total_cmp(&covariance[*b][*b]))
+        .unwrap();
+    let mut axis = covariance[widest];
+    for _ in 0..8 {
+        let mut next = [0f32; N];
+        for i in 0..N {
+            for j in 0..N {
+                next[i] += covariance[i][j] * axis[j];
+            }
+        }
+        let length = next.iter().map(|value| value * value).sum::<f32>().sqrt();
+        if length < 1e-6 {
+            return (mean, mean);
+        }
+        axis = next.map(|value| value / length);
+    }
+
+    let project =
+        |point: &[f32; N]| -> f32 { (0..N).map(|i| (point[i] - mean[i]) * axis[i]).sum() };
+    let (mut low, mut high) = (f32::MAX, f32::MIN);
+    for point in points.iter() {
+        low = low.min(project(point));
+        high = high.max(project(point));
+    }
+
+    let mut start = mean;
+    let mut end = mean;
+    for i in 0..N {
+        start[i] = (mean[i] + axis[i] * low).clamp(0.0, 255.0);
+        end[i] = (mean[i] + axis[i] * high).clamp(0.0, 255.0);
+    }
+    (start, end)
+}
+
+fn nearest<const N: usize>(value: &[u8; N], palette: &[[u8; N]]) -> usize {
+    let distance = |color: &[u8; N]| -> i32 {
+        (0..N)
+            .map(|i| (value[i] as i32 - color[i] as i32).pow(2))
+            .sum()
+    };
+    (0..palette.len())
+        .min_by_key(|index| distance(&palette[*index]))
+        .unwrap()
+}
+
+fn to_565(color: &[f32; 3]) -> u16 {
+    let r = (color[0] * 31.0 / 255.0).round() as u16;
+    let g = (color[1] * 63.0 / 255.0).round() as u16;
+    let b = (color[2] * 31.0 / 255.0).round() as u16;
+    r << 11 | g << 5 | b
+}
+
+//pixels with less than half alpha make bc1 use its three color mode with transparent black.
+fn encode_bc1_color(block: &Block, punch_through: bool) -> [u8; 8] {
+    let transparent = punch_through && block.iter().any(|pixel| pixel[3] < 128);
+    let points = block.map(|pixel| [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32]);
+    let (start, end) = principal_endpoints(&points);
+    let (mut c0, mut c1) = (to_565(&end), to_565(&start));
+
+    //four colors need c0 > c1 and three need c0 <= c1.
+    if (c0 < c1 && !transparent) || (c0 > c1 && transparent) {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+
+    let palette = bc1_palette(c0, c1, punch_through);
+    let colors = if transparent || c0 == c1 { 3 } else { 4 };
+    let rgb = palette.map(|color| [color[0], color[1], color[2]]);
+
+    let mut indices = 0u32;
+    for (pixel_index, pixel) in block.iter().enumerate() {
+        let index = if transparent && pixel[3] < 128 {
+            3
+        } else {
+            nearest(&[pixel[0], pixel[1], pixel[2]], &rgb[..colors])
+        };
+        indices |= (index as u32) << (pixel_index * 2);
+    }
+
+    let mut bytes = [0u8; 8];
+    bytes[0..2].copy_from_slice(&c0.to_le_bytes());
+    bytes[2..4].copy_from_slice(&c1.to_le_bytes());
+    bytes[4..8].copy_from_slice(&indices.to_le_bytes());
+    bytes
+}
+
+fn encode_bc1(block: &Block) -> Vec<u8> {
+    encode_bc1_color(block, true).to_vec()
+}
+
+fn encode_bc2(block: &Block) -> Vec<u8> {
+    let mut alpha = 0u64;
+    for (pixel_index, pixel) in block.iter().enumerate() {
+        alpha |= ((pixel[3] as u64 * 15 + 127) / 255) << (pixel_index * 4);
+    }
+    let mut bytes = alpha.to_le_bytes().to_vec();
+    bytes.extend(encode_bc1_color(block, false));
+    bytes
+}
+
+fn encode_bc3(block: &Block) -> Vec<u8> {
+    let mut bytes = encode_bc4_channel(&block.map(|pixel| pixel[3])).to_vec();
+    bytes.extend(encode_bc1_color(block, false));
+    bytes
+}
+
+fn encode_bc5(block: &Block) -> Vec<u8> {
+    let mut bytes = encode_bc4_channel(&block.map(|pixel| pixel[0])).to_vec();
+    bytes.extend(encode_bc4_channel(&block.map(|pixel| pixel[1])));
+    bytes
+}
+
+//the block's highest and lowest values as endpoints with the six values between them.
+fn encode_bc4_channel(values: &[u8; 16]) -> [u8; 8] {
+    let (e0, e1) = (*values.iter().max().unwrap(), *values.iter().min().unwrap());
+    let palette = bc4_palette(e0, e1).map(|value| [value]);
+
+    let mut indices = 0u64;
+    for (pixel_index, value) in values.iter().enumerate() {
+        indices |= (nearest(&[*value], &palette) as u64) << (pixel_index * 3);
+    }
+
+    let mut bytes = [0u8; 8];
+    bytes[0] = e0;
+    bytes[1] = e1;
+    bytes[2..8].copy_from_slice(&indices.to_le_bytes()[..6]);
+    bytes
+}
+
+//every block is written in mode 6: one subset of 7 bit rgba endpoints with a p-bit each and 4 bit indices.
+fn encode_bc7(block: &Block) -> Vec<u8> {
+    let points = block.map(|pixel| pixel.map(|value| value as f32));
+    let (start, end) = principal_endpoints(&points);
+
+    //each endpoint's p-bit is the lowest bit of all four channels, so it is picked for the least error.
+    let quantize = |endpoint: &[f32; 4]| -> ([u32; 4], u32) {
+        (0..2u32)
+            .map(|pbit| {
+                let values = endpoint.map(|value| {
+                    (((value - pbit as f32) / 2.0).round() as i32).clamp(0, 127) as u32
+                });
+                let error: f32 = (0..4)
+                    .map(|i| (endpoint[i] - (values[i] << 1 | pbit) as f32).powi(2))
+                    .sum();
+                (values, pbit, error)
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(values, pbit, _error)| (values, pbit))
+            .unwrap()
+    };
+    let ((start, start_pbit), (end, end_pbit)) = (quantize(&start), quantize(&end));
+    let (mut endpoints, mut pbits) = ([start, end], [start_pbit, end_pbit]);
+
+    let palette_for = |endpoints: &[[u32; 4]; 2], pbits: &[u32; 2]| -> Vec<[u8; 4]> {
+        let e0 = endpoints[0].map(|value| (value << 1 | pbits[0]) as u8);
+        let e1 = endpoints[1].map(|value| (value << 1 | pbits[1]) as u8);
+        (0..16)
+            .map(|index| [0, 1, 2, 3].map(|i| bc7_interpolate(e0[i], e1[i], index, 4)))
+            .collect()
+    };
+    let mut indices = block.map(|pixel| nearest(&pixel, &palette_for(&endpoints, &pbits)) as u32);
+
+    //the first pixel's index is stored without its top bit, so the endpoints are swapped if it is set.
+    if indices[0] >= 8 {
+        endpoints.swap(0, 1);
+        pbits.swap(0, 1);
+        indices = indices.map(|index| 15 - index);
+    }
+
+    let (mut bits, mut position) = (1u128 << 6, 7);
+    let mut write = |value: u32, count: u32| {
+        bits |= (value as u128) << position;
+        position += count;
+    };
+    for (start, end) in endpoints[0].iter().zip(endpoints[1].iter()) {
+        write(*start, 7);
+        write(*end, 7);
+    }
+    write(pbits[0], 1);
+    write(pbits[1], 1);
+    for (pixel, index) in indices.iter().enumerate() {
+        write(*index, if pixel == 0 { 3 } else { 4 });
+    }
+    bits.to_le_bytes().to_vec()
+}
@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::bundle_index::as_hex;
+use crate::file_filter::FileFilter;
+use crate::resource_index::{ResourceIndex, ResourceLocation};
+use crate::unbundler::{Unbundler, UnbundlerError};
+
+type ResourceKey = (u64, u64); //(extension, path)
+
+//what changed between two versions of a bundle or a directory of bundles.
+#[derive(Serialize)]
+pub struct BundleDiff {
+    pub changes: Vec<ResourceChange>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Serialize)]
+pub struct ResourceChange {
+    pub kind: ChangeKind,
+    #[serde(serialize_with = "as_hex")]
+    pub extension_hash: u64,
+    pub extension: String,
+    #[serde(serialize_with = "as_hex")]
+    pub path: u64,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    pub size_delta: i64,
+    pub bytes: Option<ByteSummary>, //only for modified resources.
+}
+
+#[derive(Serialize)]
+pub struct ByteSummary {
+    pub first_difference: Option<u64>, //offset into the data of every variant laid end to end.
+    pub differing_bytes: u64,
+}
+
+impl BundleDiff {
+    //resources are matched by extension and path. when a resource is in more than one bundle,
+    //it is modified if the set of distinct copies changed, and its bytes are compared using the first copy.
+    pub fn diff(
+        old: &Unbundler,
+        new: &Unbundler,
+        jobs: usize,
+    ) -> Result<BundleDiff, UnbundlerError> {
+        let old_index = ResourceIndex::build(old, jobs)?;
+        let new_index = ResourceIndex::build(new, jobs)?;
+        let old_resources = BundleDiff::by_key(&old_index);
+        let new_resources = BundleDiff::by_key(&new_index);
+
+        let keys: BTreeSet<&ResourceKey> =
+            old_resources.keys().chain(new_resources.keys()).collect();
+
+        let mut changes = vec![];
+        for key in keys {
+            let change = match (old_resources.get(key), new_resources.get(key)) {
+                (Some(old), None) => BundleDiff::change(ChangeKind::Removed, old, Some(old), None),
+                (None, Some(new)) => BundleDiff::change(ChangeKind::Added, new, None, Some(new)),
+                (Some(old), Some(new)) if BundleDiff::digests(old) != BundleDiff::digests(new) => {
+                    BundleDiff::change(ChangeKind::Modified, new, Some(old), Some(new))
+                }
+                _ => continue,
+            };
+            changes.push(change);
+        }
+
+        let modified: BTreeSet<ResourceKey> = changes
+            .iter()
+            .filter(|change| change.kind == ChangeKind::Modified)
+            .map(|change| (change.extension_hash, change.path))
+            .collect();
+
+        if !modified.is_empty() {
+            let old_data = BundleDiff::read_data(old, &modified)?;
+            let new_data = BundleDiff::read_data(new, &modified)?;
+
+            for change in changes.iter_mut() {
+                let key = (change.extension_hash, change.path);
+                if let (Some(old), Some(new)) = (old_data.get(&key), new_data.get(&key)) {
+                    change.bytes = Some(ByteSummary::new(old, new));
+                }
+            }
+        }
+
+        Ok(BundleDiff { changes })
+    }
+
+    fn by_key(index: &ResourceIndex) -> BTreeMap<ResourceKey, &ResourceLocation> {
+        index
+            .resources
+            .iter()
+            .map(|resource| ((resource.extension_hash, resource.path), resource))
+            .collect()
+    }
+
+    fn digests(resource: &ResourceLocation) -> BTreeSet<u64> {
+        resource.copies.iter().map(|copy| copy.digest).collect()
+    }
+
+    fn change(
+        kind: ChangeKind,
+        resource: &ResourceLocation,
+        old: Option<&ResourceLocation>,
+        new: Option<&ResourceLocation>,
+    ) -> ResourceChange {
+        let old_size = old.map(|old| old.copies[0].size);
+        let new_size = new.map(|new| new.copies[0].size);
+
+        ResourceChange {
+            kind,
+            extension_hash: resource.extension_hash,
+            extension: resource.extension.clone(),
+            path: resource.path,
+            old_size,
+            new_size,
+            size_delta: new_size.unwrap_or(0) as i64 - old_size.unwrap_or(0) as i64,
+            bytes: None,
+        }
+    }
+
+    //reads the first copy of every modified resource. everything else is skipped by the filter.
+    fn read_data(
+        unbundler: &Unbundler,
+        keys: &BTreeSet<ResourceKey>,
+    ) -> Result<BTreeMap<ResourceKey, Vec<u8>>, UnbundlerError> {
+        let mut selective = unbundler.clone();
+        selective.filter = FileFilter {
+            paths: keys.iter().map(|(_extension, path)| *path).collect(),
+            ..unbundler.filter.clone()
+        };
+
+        let mut data = BTreeMap::new();
+        selective.for_each_file(|_bundle_name, file| {
+            let key = (file.header.extension, file.path);
+            if keys.contains(&key) {
+                data.entry(key).or_insert_with(|| {
+                    file.variants
+                        .iter()
+                        .flat_map(|variant| variant.data.iter().copied())
+                        .collect()
+                });
+            }
+        })?;
+
+        Ok(data)
+    }
+}
+
+impl ByteSummary {
+    pub fn new(old: &[u8], new: &[u8]) -> ByteSummary {
+        let mut first_difference = None;
+        let mut differing_bytes = old.len().abs_diff(new.len()) as u64;
+
+        for (offset, (old_byte, new_byte)) in old.iter().zip(new.iter()).enumerate() {
+            if old_byte != new_byte {
+                first_difference.get_or_insert(offset as u64);
+                differing_bytes += 1;
+            }
+        }
+
+        if first_difference.is_none() && old.len() != new.len() {
+            first_difference = Some(old.len().min(new.len()) as u64);
+        }
+
+        ByteSummary {
+            first_difference,
+            differing_bytes,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+pub const DEFAULT_VERSION: u32 = 0xf0000004;
+
+#[derive(Clone)]
+pub struct BundleHeader {
+    pub version: u32,
+    pub inflated_size: u32, //declared size of the inflated stream.
+    pub reserved: u32,
+    pub checksum: Vec<u8>,
+    pub names: Vec<(u64, u64)>, //(extension, path) for every file in the bundle.
+}
+
+impl Default for BundleHeader {
+    fn default() -> BundleHeader {
+        BundleHeader {
+            version: DEFAULT_VERSION,
+            inflated_size: 0,
+            reserved: 0,
+            checksum: vec![0; 256],
+            names: vec![],
+        }
+    }
+}
+
+//language, size and flags are stored per variant on the UnbundledFile.
+#[derive(Clone)]
+pub struct BundleEntryHeader {
+    pub extension: u64,
+    pub path: u64,
+    pub variant_count: u32,
+    pub reserved: u32, //follows the variant count. it is 0 in mww bundles and written back as it is.
+}
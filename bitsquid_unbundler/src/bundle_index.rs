@@ -0,0 +1,29 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+//what is in a bundle, without the data of its files.
+#[derive(Serialize)]
+pub struct BundleIndex {
+    pub bundle_name: String,
+    pub entries: Vec<IndexEntry>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct IndexEntry {
+    #[serde(serialize_with = "as_hex")]
+    pub extension_hash: u64,
+    pub extension: String,
+    #[serde(serialize_with = "as_hex")]
+    pub path: u64,
+    pub variant_count: u32,
+    pub size: u64, //sum of the sizes of every variant.
+}
+
+//hashes are written the same way the FileWriter names files, so they can be grepped for.
+pub fn as_hex<S: Serializer>(hash: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:#x}", hash))
+}
+
+pub fn from_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let hash = String::deserialize(deserializer)?;
+    u64::from_str_radix(hash.trim_start_matches("0x"), 16).map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::bundle_header::BundleHeader;
+use crate::bundle_index::{as_hex, from_hex};
+use crate::unbundled_directory::UnbundledDirectory;
+use crate::unbundled_file::UnbundledFile;
+use crate::unbundler::UnbundlerError;
+
+//the name of the manifest in an unbundled directory.
+pub const MANIFEST_NAME: &str = "bundle.json";
+
+//what file names cannot hold: the bundle header, the order of the files and the flags of every entry and variant.
+//the FileWriter saves it beside the files and the bundler restores the bundle from it.
+//it also lists the images and tables exported beside the resources, so that only edited ones are imported.
+#[derive(Serialize, Deserialize, Default)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub reserved: u32,
+    #[serde(serialize_with = "as_hex_bytes", deserialize_with = "from_hex_bytes")]
+    pub checksum: Vec<u8>,
+    pub files: Vec<ManifestFile>, //in the order of the bundle.
+    #[serde(default)]
+    pub exports: Vec<ManifestExport>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ManifestFile {
+    #[serde(serialize_with = "as_hex", deserialize_with = "from_hex")]
+    pub extension: u64,
+    #[serde(serialize_with = "as_hex", deserialize_with = "from_hex")]
+    pub path: u64,
+    #[serde(default)]
+    pub reserved: u32,
+    pub variants: Vec<ManifestVariant>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ManifestVariant {
+    pub language: u32,
+    pub flags: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ManifestExport {
+    pub name: String, //relative to the directory, with / like the bundler's file names.
+    #[serde(serialize_with = "as_hex", deserialize_with = "from_hex")]
+    pub digest: u64,
+}
+
+impl BundleManifest {
+    pub fn set_header(&mut self, header: &BundleHeader) {
+        self.version = header.version;
+        self.reserved = header.reserved;
+        self.checksum = header.checksum.clone();
+    }
+
+    pub fn push(&mut self, file: &UnbundledFile) {
+        self.files.push(ManifestFile {
+            extension: file.header.extension,
+            path: file.path,
+            reserved: file.header.reserved,
+            variants: file
+                .variants
+                .iter()
+                .map(|variant| ManifestVariant {
+                    language: variant.language,
+                    flags: variant.flags,
+                })
+                .collect(),
+        });
+    }
+
+    pub fn export(&mut self, name: String, data: &[u8]) {
+        self.exports.push(ManifestExport {
+            name,
+            digest: BundleManifest::digest(data),
+        });
+    }
+
+    //an export which is still as the FileWriter wrote it replaces nothing.
+    pub fn is_unchanged(&self, name: &str, data: &[u8]) -> bool {
+        let digest = BundleManifest::digest(data);
+        self.exports
+            .iter()
+            .any(|export| export.name == name && export.digest == digest)
+    }
+
+    //fnv-1a, as the resource index uses.
+    fn digest(data: &[u8]) -> u64 {
+        data.iter().fold(0xcbf29ce484222325, |digest, byte| {
+            (digest ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    //restores the header and the flags of every entry and variant the manifest lists.
+    pub fn restore(&self, unbundled_dir: &mut UnbundledDirectory) {
+        unbundled_dir.header.version = self.version;
+        unbundled_dir.header.reserved = self.reserved;
+        unbundled_dir.header.checksum = self.checksum.clone();
+
+        for file in unbundled_dir.files.iter_mut() {
+            let Some(listed) = self.files.get(self.position(file)) else {
+                continue;
+            };
+            file.header.reserved = listed.reserved;
+            for variant in file.variants.iter_mut() {
+                if let Some(listed) = listed
+                    .variants
+                    .iter()
+                    .find(|listed| listed.language == variant.language)
+                {
+                    variant.flags = listed.flags;
+                }
+            }
+        }
+    }
+
+    //puts the files back in the order of the bundle. files the manifest does not list,
+    //such as ones added by hand, are kept after the others.
+    pub fn sort(&self, unbundled_dir: &mut UnbundledDirectory) {
+        unbundled_dir.files.sort_by_key(|file| self.position(file));
+    }
+
+    fn position(&self, file: &UnbundledFile) -> usize {
+        self.files
+            .iter()
+            .position(|listed| {
+                listed.extension == file.header.extension && listed.path == file.path
+            })
+            .unwrap_or(self.files.len())
+    }
+
+    pub fn load(manifest_path: &Path) -> Result<BundleManifest, UnbundlerError> {
+        let json = fs::read_to_string(manifest_path)?;
+        serde_json::from_str(&json).map_err(|e| UnbundlerError::Json(e.to_string()))
+    }
+
+    pub fn save(&self, manifest_path: &Path) -> Result<(), UnbundlerError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| UnbundlerError::Json(e.to_string()))?;
+        fs::write(manifest_path, json)?;
+        Ok(())
+    }
+}
+
+fn as_hex_bytes<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    serializer.serialize_str(&hex)
+}
+
+fn from_hex_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return Err(serde::de::Error::custom("the checksum is not hex"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(serde::de::Error::custom))
+        .collect()
+}
@@ -0,0 +1,249 @@
+use std::fs::File;
+use std::io::{BufRead, Read, Seek, SeekFrom};
+
+use crate::bundle_header::{BundleEntryHeader, BundleHeader};
+use crate::bundle_index::IndexEntry;
+use crate::byte_stream::ByteStream;
+use crate::file_filter::FileFilter;
+use crate::game_profile::GameProfile;
+use crate::inflater::Inflater;
+use crate::unbundled_file::{ResourceVariant, UnbundledFile};
+use crate::unbundler::UnbundlerError;
+
+type VariantHeader = (u32, u32, u32); //(language, size, flags)
+
+//reads the files of a bundle one at a time as the compressed stream is inflated.
+pub struct BundleReader<R: BufRead> {
+    pub header: BundleHeader,
+    pub profile: GameProfile,
+    inflated_stream: ByteStream<Inflater<R>>,
+    remaining: usize,
+    filter: FileFilter,
+    stream_file: Option<File>, //the bundle's .stream file.
+    stream_offset: u64,        //where the next variant's streamed data starts in it.
+}
+
+impl<R: BufRead> BundleReader<R> {
+    //without a profile, the profile is picked by the version in the bundle header.
+    pub fn new(
+        reader: R,
+        profile: Option<GameProfile>,
+        filter: FileFilter,
+    ) -> Result<BundleReader<R>, UnbundlerError> {
+        let mut compressed_stream = ByteStream::from_reader(reader);
+        let mut header = BundleHeader {
+            version: compressed_stream.read_uint()?,
+            inflated_size: compressed_stream.read_uint()?,
+            reserved: compressed_stream.read_uint()?,
+            checksum: vec![],
+            names: vec![],
+        };
+        let profile = profile.unwrap_or_else(|| GameProfile::detect(header.version));
+
+        let mut inflated_stream =
+            ByteStream::from_reader(Inflater::new(compressed_stream, profile.block_size));
+        let file_count = inflated_stream.read_uint()?;
+        header.checksum = inflated_stream.read(profile.checksum_size)?;
+
+        for _i in 0..file_count {
+            let extension = inflated_stream.read_ulong()?;
+            let path = inflated_stream.read_ulong()?;
+            inflated_stream.skip(profile.name_entry_size.saturating_sub(16))?;
+            header.names.push((extension, path));
+        }
+
+        Ok(BundleReader {
+            header,
+            profile,
+            inflated_stream,
+            remaining: file_count as usize,
+            filter,
+            stream_file: None,
+            stream_offset: 0,
+        })
+    }
+
+    //streamed data is stored one variant after the other in the order of the bundle, so each
+    //variant's offset is the sum of the stream sizes in the flags before it. only that layout is supported:
+    //nothing in the bundle or the resources says where the data is, since texture stream references
+    //are offsets into the variant's own data. a .stream file laid out any other way is an error once
+    //every file has been read and the sizes do not add up to its length.
+    pub fn with_stream(mut self, stream_file: File) -> BundleReader<R> {
+        self.stream_file = Some(stream_file);
+        self
+    }
+
+    //offset into the inflated stream. once every file has been read this is where the last file ends.
+    pub fn position(&self) -> usize {
+        self.inflated_stream.position()
+    }
+
+    //inflates whatever is left of the stream after the current position.
+    pub fn read_remaining(&mut self) -> Result<Vec<u8>, UnbundlerError> {
+        self.inflated_stream.read_to_end()
+    }
+
+    //reads the metadata of the remaining files, skipping over their data without keeping it.
+    pub fn read_index_entries(&mut self) -> Result<Vec<IndexEntry>, UnbundlerError> {
+        let mut entries = vec![];
+
+        while self.remaining > 0 {
+            match self.read_index_entry() {
+                Ok(Some(entry)) => entries.push(entry),
+                Ok(None) => {}
+                Err(e) => {
+                    self.remaining = 0;
+                    return Err(e);
+                }
+            }
+            self.remaining -= 1;
+        }
+
+        Ok(entries)
+    }
+
+    fn read_index_entry(&mut self) -> Result<Option<IndexEntry>, UnbundlerError> {
+        let (header, variant_headers) = self.read_entry_header()?;
+        let selected = self.filter.matches(header.extension, header.path);
+
+        let mut size = 0;
+        for (_language, variant_size, _flags) in variant_headers {
+            self.inflated_stream.skip(variant_size as usize)?;
+            size += variant_size as u64;
+        }
+
+        if !selected {
+            return Ok(None);
+        }
+
+        Ok(Some(IndexEntry {
+            extension_hash: header.extension,
+            extension: self.profile.extension(header.extension),
+            path: header.path,
+            variant_count: header.variant_count,
+            size,
+        }))
+    }
+
+    //files which do not pass the filter are skipped without copying their data.
+    fn read_unbundled_file(&mut self) -> Result<Option<UnbundledFile>, UnbundlerError> {
+        let (header, variant_headers) = self.read_entry_header()?;
+
+        if !self.filter.matches(header.extension, header.path) {
+            for (_language, size, flags) in variant_headers {
+                self.inflated_stream.skip(size as usize)?;
+                self.stream_offset += flags as u64;
+            }
+            return Ok(None);
+        }
+
+        let mut variants = vec![];
+        for (language, size, flags) in variant_headers {
+            variants.push(ResourceVariant {
+                language,
+                flags,
+                data: self.inflated_stream.read(size as usize)?,
+                stream: self.read_stream(flags)?,
+            });
+        }
+
+        Ok(Some(UnbundledFile {
+            extension: self.profile.extension(header.extension),
+            path: header.path,
+            variants,
+            header,
+        }))
+    }
+
+    //bundles without a .stream file keep whatever is in the field and have no streamed data.
+    fn read_stream(&mut self, size: u32) -> Result<Vec<u8>, UnbundlerError> {
+        let Some(stream_file) = self.stream_file.as_mut() else {
+            return Ok(vec![]);
+        };
+        if size == 0 {
+            return Ok(vec![]);
+        }
+
+        let offset = self.stream_offset;
+        stream_file.seek(SeekFrom::Start(offset))?;
+        let mut stream = vec![];
+        stream_file.take(size as u64).read_to_end(&mut stream)?;
+        self.stream_offset += size as u64;
+
+        if stream.len() < size as usize {
+            return Err(UnbundlerError::UnexpectedEof {
+                offset: offset as usize,
+                wanted: size as usize,
+            });
+        }
+        Ok(stream)
+    }
+
+    //runs once, after the last file. the file is dropped so that it is not checked again.
+    fn check_stream_length(&mut self) -> Result<(), UnbundlerError> {
+        let Some(stream_file) = self.stream_file.take() else {
+            return Ok(());
+        };
+
+        let length = stream_file.metadata()?.len();
+        if length != self.stream_offset {
+            return Err(UnbundlerError::Stream(format!(
+                "the .stream file is {} bytes, but the bundle's stream sizes add up to {}. its data is not stored one variant after the other.",
+                length, self.stream_offset
+            )));
+        }
+        Ok(())
+    }
+
+    fn read_entry_header(
+        &mut self,
+    ) -> Result<(BundleEntryHeader, Vec<VariantHeader>), UnbundlerError> {
+        let inflated_stream = &mut self.inflated_stream;
+        let extension = inflated_stream.read_ulong()?;
+        let path = inflated_stream.read_ulong()?;
+        let variant_count = inflated_stream.read_uint()?;
+        let reserved = inflated_stream.read_uint()?;
+
+        //every variant header comes before the data of the first variant.
+        let mut variant_headers = vec![];
+        for _i in 0..variant_count {
+            let language = inflated_stream.read_uint()?;
+            let size = inflated_stream.read_uint()?;
+            let flags = inflated_stream.read_uint()?;
+            variant_headers.push((language, size, flags));
+        }
+
+        let header = BundleEntryHeader {
+            extension,
+            path,
+            variant_count,
+            reserved,
+        };
+        Ok((header, variant_headers))
+    }
+}
+
+impl<R: BufRead> Iterator for BundleReader<R> {
+    type Item = Result<UnbundledFile, UnbundlerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            match self.read_unbundled_file() {
+                Ok(unbundled_file) => {
+                    self.remaining -= 1;
+                    if unbundled_file.is_some() {
+                        return unbundled_file.map(Ok);
+                    }
+                }
+                Err(e) => {
+                    //the stream cannot be resynchronized after a bad entry.
+                    self.remaining = 0;
+                    self.stream_file = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        self.check_stream_length().err().map(Err)
+    }
+}
@@ -0,0 +1,528 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use murmur32_gen::murmur::hash64;
+
+use crate::bundle_header::{BundleEntryHeader, BundleHeader};
+use crate::bundle_manifest::{BundleManifest, MANIFEST_NAME};
+use crate::extension_registry::{ExtensionRegistry, STRINGS, TEXTURE};
+use crate::game_profile::GameProfile;
+use crate::strings::{StringTable, StringsFormat};
+use crate::texture::Texture;
+use crate::unbundled_directory::UnbundledDirectory;
+use crate::unbundled_file::{ResourceVariant, UnbundledFile};
+use crate::unbundler::{Unbundler, UnbundlerError};
+
+pub struct Bundler {
+    pub file_path: PathBuf,
+    pub profile: Option<GameProfile>, //detected from the header version when not given.
+}
+
+impl Bundler {
+    pub fn bundle(&self, unbundled_dir: &UnbundledDirectory) -> Result<(), UnbundlerError> {
+        let profile = match &self.profile {
+            Some(profile) => profile.clone(),
+            None => GameProfile::detect(unbundled_dir.header.version),
+        };
+
+        //streamed data goes to the .stream file in the same order as the variants in the bundle.
+        let mut stream = vec![];
+        for file in unbundled_dir.files.iter() {
+            for variant in file.variants.iter() {
+                stream.extend(&variant.stream);
+            }
+        }
+        if !stream.is_empty() && !profile.streams {
+            return Err(UnbundlerError::Profile(format!(
+                "{} bundles have no .stream file, but some of the files have streamed data.",
+                profile.name
+            )));
+        }
+
+        let inflated = Bundler::write_unbundled_files(unbundled_dir, &profile)?;
+        let compressed =
+            Bundler::deflate_stream(&unbundled_dir.header, &inflated, profile.block_size)?;
+        fs::write(&self.file_path, compressed)?;
+        if !stream.is_empty() {
+            fs::write(Unbundler::stream_path(&self.file_path), stream)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn bundle_directory(&self, dir_path: &Path) -> Result<(), UnbundlerError> {
+        let unbundled_dir = Bundler::read_directory(dir_path)?;
+        self.bundle(&unbundled_dir)
+    }
+
+    //reads a directory written by the FileWriter. files are named {:#x}.{extension}
+    //or {:#x}.lang_XX.{extension} for localized variants. files with restored names, such as
+    //scripts/game/player.lua, are found in subdirectories and hashed back into their path.
+    //streamed data is read from a companion file with .stream added to the variant's name.
+    //the header, the file order and the variant flags come from bundle.json, if the FileWriter saved one.
+    pub fn read_directory(dir_path: &Path) -> Result<UnbundledDirectory, UnbundlerError> {
+        let dir_name = dir_path
+            .file_name()
+            .ok_or(UnbundlerError::NoFileName)?
+            .to_str()
+            .ok_or(UnbundlerError::NotUTF8)?;
+
+        let mut entries = vec![];
+        Bundler::find_files(dir_path, "", &mut entries)?;
+        entries.sort();
+        entries.retain(|(file_name, _)| file_name != MANIFEST_NAME);
+
+        let manifest_path = dir_path.join(MANIFEST_NAME);
+        let manifest = if manifest_path.is_file() {
+            Some(BundleManifest::load(&manifest_path)?)
+        } else {
+            None
+        };
+        let unchanged = |file_name: &str, data: &[u8]| {
+            manifest
+                .as_ref()
+                .is_some_and(|manifest| manifest.is_unchanged(file_name, data))
+        };
+
+        let mut unbundled_dir =
+            UnbundledDirectory::new(String::from(dir_name), BundleHeader::default(), vec![]);
+        let mut replacements = vec![];
+        let mut faces: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (file_name, file_path) in entries {
+            let (file_name, is_stream) = match file_name.strip_suffix(".stream") {
+                Some(file_name) => (file_name.to_string(), true),
+                None => (file_name, false),
+            };
+            let (path, language, extension) = Bundler::parse_file_name(&file_name)?;
+            let data = fs::read(&file_path)?;
+
+            //replacements are applied once the resources they replace have been read.
+            let replacement = match extension.as_str() {
+                "png" | "csv" | "json" => true,
+                "dds" => data.starts_with(b"DDS "),
+                _ => false,
+            };
+            //images and tables which were exported but not edited are left out,
+            //so that the resource beside them is bundled as it was.
+            if replacement && !is_stream {
+                if unchanged(&file_name, &data) {
+                    continue;
+                }
+                replacements.push((
+                    file_name,
+                    path,
+                    language.unwrap_or(0),
+                    extension,
+                    vec![data],
+                ));
+                continue;
+            }
+
+            //the faces of a cubemap written with --faces are imported together.
+            if let (Some(face), false) = (Bundler::face_index(&extension), is_stream) {
+                let key = (path, language.unwrap_or(0));
+                faces.entry(key).or_default().push((face, file_name, data));
+                continue;
+            }
+
+            let index = Bundler::file_index(&mut unbundled_dir, path, extension)?;
+            let file = &mut unbundled_dir.files[index];
+
+            //a companion is read after its variant since names are sorted, unless the variant was empty.
+            if is_stream {
+                let language = language.unwrap_or(0);
+                match file.variants.iter_mut().find(|v| v.language == language) {
+                    Some(variant) => variant.stream = data,
+                    None => file.variants.push(ResourceVariant {
+                        language,
+                        flags: 0,
+                        data: vec![],
+                        stream: data,
+                    }),
+                }
+                file.variants.sort_by_key(|variant| variant.language);
+                file.header.variant_count = file.variants.len() as u32;
+                continue;
+            }
+
+            //an empty file without a language is what the FileWriter writes for an entry without variants.
+            if language.is_none() && data.is_empty() {
+                continue;
+            }
+
+            file.variants.push(ResourceVariant {
+                language: language.unwrap_or(0),
+                flags: 0,
+                data,
+                stream: vec![],
+            });
+            file.variants.sort_by_key(|variant| variant.language);
+            file.header.variant_count = file.variants.len() as u32;
+        }
+
+        //the header and flags are restored before replacements, which clear the flags of what they change.
+        if let Some(manifest) = &manifest {
+            manifest.restore(&mut unbundled_dir);
+        }
+
+        for ((path, language), mut images) in faces {
+            if images.iter().all(|(_, name, data)| unchanged(name, data)) {
+                continue;
+            }
+            images.sort_by_key(|(face, _, _)| *face);
+            if !images.iter().map(|(face, _, _)| *face).eq(0..6) {
+                return Err(UnbundlerError::Texture(format!(
+                    "{} is not one of the six faces face0 to face5 of a cubemap",
+                    images[0].1
+                )));
+            }
+            let file_name = images[0].1.clone();
+            let images = images.into_iter().map(|(_, _, data)| data).collect();
+            replacements.push((file_name, path, language, "png".to_string(), images));
+        }
+
+        for (file_name, path, language, extension, images) in replacements {
+            let message = |e| format!("{} could not be imported: {}", file_name, e);
+            match StringsFormat::from_extension(&extension) {
+                Some(format) => {
+                    Bundler::replace_strings(&mut unbundled_dir, path, language, format, &images[0])
+                        .map_err(|e| UnbundlerError::Strings(message(e)))?
+                }
+                None => Bundler::replace_texture(
+                    &mut unbundled_dir,
+                    path,
+                    language,
+                    &extension,
+                    &images,
+                )
+                .map_err(|e| UnbundlerError::Texture(message(e)))?,
+            }
+        }
+
+        if let Some(manifest) = &manifest {
+            manifest.sort(&mut unbundled_dir);
+        }
+        Ok(unbundled_dir)
+    }
+
+    fn file_index(
+        unbundled_dir: &mut UnbundledDirectory,
+        path: u64,
+        extension: String,
+    ) -> Result<usize, UnbundlerError> {
+        if let Some(index) = unbundled_dir
+            .files
+            .iter()
+            .position(|file| file.path == path && file.extension == extension)
+        {
+            return Ok(index);
+        }
+
+        let extension_hash = ExtensionRegistry::global()
+            .hash(&extension)
+            .ok_or_else(|| UnbundlerError::Extension(extension.clone()))?;
+        unbundled_dir.push(UnbundledFile {
+            path,
+            extension,
+            variants: vec![],
+            header: BundleEntryHeader {
+                extension: extension_hash,
+                path,
+                variant_count: 0,
+                reserved: 0,
+            },
+        });
+        Ok(unbundled_dir.files.len() - 1)
+    }
+
+    //a .csv or .json exported beside a strings resource replaces its entries.
+    fn replace_strings(
+        unbundled_dir: &mut UnbundledDirectory,
+        path: u64,
+        language: u32,
+        format: StringsFormat,
+        data: &[u8],
+    ) -> Result<(), UnbundlerError> {
+        let variant = unbundled_dir
+            .files
+            .iter_mut()
+            .filter(|file| file.path == path && file.header.extension == STRINGS)
+            .flat_map(|file| file.variants.iter_mut())
+            .find(|variant| variant.language == language)
+            .ok_or_else(|| {
+                UnbundlerError::Strings(
+                    "a string table needs the strings resource it replaces beside it".to_string(),
+                )
+            })?;
+
+        let text = std::str::from_utf8(data).map_err(|_| UnbundlerError::NotUTF8)?;
+        let mut table = StringTable::parse(&variant.data)?;
+        table.import(format, text)?;
+        variant.data = table.to_bytes();
+        Ok(())
+    }
+
+    //a .png or .dds beside a texture replaces it, encoded in the texture's format with its flags
+    //and with mips if it has them. every mip is embedded, so the texture no longer streams.
+    //a .dds without a texture beside it, as written with --dds, becomes a texture of its own.
+    //six pngs are the faces of a cubemap, in order.
+    fn replace_texture(
+        unbundled_dir: &mut UnbundledDirectory,
+        path: u64,
+        language: u32,
+        extension: &str,
+        images: &[Vec<u8>],
+    ) -> Result<(), UnbundlerError> {
+        let original = unbundled_dir
+            .files
+            .iter_mut()
+            .filter(|file| file.path == path && file.header.extension == TEXTURE)
+            .flat_map(|file| file.variants.iter_mut())
+            .find(|variant| variant.language == language);
+
+        let Some(variant) = original else {
+            if extension == "png" {
+                return Err(UnbundlerError::Texture(
+                    "a png needs the texture it replaces beside it".to_string(),
+                ));
+            }
+
+            //.dds is only how the FileWriter writes textures, the resource is a texture.
+            let index = Bundler::file_index(unbundled_dir, path, "texture".to_string())?;
+            let file = &mut unbundled_dir.files[index];
+            file.variants.push(ResourceVariant {
+                language,
+                flags: 0,
+                data: Texture::from_dds(&images[0])?.to_resource(),
+                stream: vec![],
+            });
+            file.variants.sort_by_key(|variant| variant.language);
+            file.header.variant_count = file.variants.len() as u32;
+            return Ok(());
+        };
+
+        let original = Texture::parse(&variant.data, &variant.stream)?;
+        let replacement = if extension == "png" {
+            //a single png of a cubemap or volume texture only has one face or its stacked slices.
+            if images.len() == 1 && (original.cubemap || original.depth > 1) {
+                eprintln!(
+                    "Keeping the original of {:#x}, a png can only replace a 2d texture.",
+                    path
+                );
+                return Ok(());
+            }
+
+            let mut faces = images
+                .iter()
+                .map(|image| {
+                    let (width, height, pixels) = Texture::read_png(image)?;
+                    Texture::from_rgba(
+                        original.format,
+                        width,
+                        height,
+                        pixels,
+                        original.mip_count > 1,
+                    )
+                })
+                .collect::<Result<Vec<Texture>, UnbundlerError>>()?;
+            match faces.len() {
+                1 => faces.remove(0),
+                _ => Texture::cubemap(faces)?,
+            }
+        } else {
+            Texture::from_dds(&images[0])?
+        };
+
+        variant.data = replacement.matching(&original)?.to_resource();
+        variant.stream.clear();
+        variant.flags = 0;
+        Ok(())
+    }
+
+    //the face written with --faces, from an extension such as face3.png.
+    fn face_index(extension: &str) -> Option<usize> {
+        extension
+            .strip_prefix("face")?
+            .strip_suffix(".png")?
+            .parse()
+            .ok()
+    }
+
+    //file names are relative to the bundle directory and always use / like the engine does.
+    fn find_files(
+        dir_path: &Path,
+        prefix: &str,
+        entries: &mut Vec<(String, PathBuf)>,
+    ) -> Result<(), UnbundlerError> {
+        for entry in dir_path.read_dir()? {
+            let entry_path = entry?.path();
+            let name = entry_path
+                .file_name()
+                .ok_or(UnbundlerError::NoFileName)?
+                .to_str()
+                .ok_or(UnbundlerError::NotUTF8)?;
+            let name = format!("{}{}", prefix, name);
+
+            if entry_path.is_dir() {
+                Bundler::find_files(&entry_path, &format!("{}/", name), entries)?;
+            } else if entry_path.is_file() {
+                entries.push((name, entry_path));
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_file_name(file_name: &str) -> Result<(u64, Option<u32>, String), UnbundlerError> {
+        let invalid = || UnbundlerError::InvalidFileName(file_name.to_string());
+
+        //only the last part of the name is split, since directories may contain dots.
+        let (directory, file_name) = match file_name.rsplit_once('/') {
+            Some((directory, file_name)) => (Some(directory), file_name),
+            None => (None, file_name),
+        };
+
+        //names are split from the right, since restored names may contain dots: name[.lang_XX][.faceN].extension
+        let (name, extension) = file_name.rsplit_once('.').ok_or_else(invalid)?;
+        let (name, extension) = match name.rsplit_once('.') {
+            Some((name, face))
+                if Bundler::face_index(&format!("{}.{}", face, extension)).is_some() =>
+            {
+                (name, format!("{}.{}", face, extension))
+            }
+            _ => (name, extension.to_string()),
+        };
+        let localized = name
+            .rsplit_once('.')
+            .and_then(|(name, language)| Some((name, language.strip_prefix("lang_")?)));
+        let (name, language) = match localized {
+            Some((name, language)) => (name, Some(language.parse::<u32>().map_err(|_| invalid())?)),
+            None => (name, None),
+        };
+
+        let path = match (directory, name.strip_prefix("0x")) {
+            (None, Some(hex)) => u64::from_str_radix(hex, 16).map_err(|_| invalid())?,
+            (None, None) => hash64(name),
+            (Some(directory), _) => hash64(&format!("{}/{}", directory, name)),
+        };
+
+        Ok((path, language, extension))
+    }
+
+    //the name table, variant counts and sizes are rebuilt from the files. everything else comes from the headers.
+    //the checksum and name table entries are sized by the profile. name entries are padded with zeros.
+    fn write_unbundled_files(
+        unbundled_dir: &UnbundledDirectory,
+        profile: &GameProfile,
+    ) -> Result<Vec<u8>, UnbundlerError> {
+        let mut checksum = unbundled_dir.header.checksum.clone();
+        checksum.resize(profile.checksum_size, 0);
+
+        let mut inflated: Vec<u8> = vec![];
+        inflated.extend((unbundled_dir.files.len() as u32).to_le_bytes());
+        inflated.extend(checksum);
+
+        for file in unbundled_dir.files.iter() {
+            inflated.extend(file.header.extension.to_le_bytes());
+            inflated.extend(file.path.to_le_bytes());
+            inflated.extend(vec![0; profile.name_entry_size.saturating_sub(16)]);
+        }
+
+        let has_stream = unbundled_dir
+            .files
+            .iter()
+            .any(|file| file.variants.iter().any(|v| !v.stream.is_empty()));
+        for file in unbundled_dir.files.iter() {
+            inflated.extend(file.header.extension.to_le_bytes());
+            inflated.extend(file.path.to_le_bytes());
+
+            inflated.extend((file.variants.len() as u32).to_le_bytes());
+            inflated.extend(file.header.reserved.to_le_bytes());
+
+            //with a .stream file the flags are the stream sizes taken from the data, otherwise they are kept as they were.
+            for variant in file.variants.iter() {
+                let name = || format!("{:#x}.{}", file.path, file.extension);
+                let flags = if has_stream {
+                    Bundler::size(variant.stream.len(), || {
+                        format!("The streamed data of {}", name())
+                    })?
+                } else {
+                    variant.flags
+                };
+                inflated.extend(variant.language.to_le_bytes());
+                inflated.extend(Bundler::size(variant.data.len(), name)?.to_le_bytes());
+                inflated.extend(flags.to_le_bytes());
+            }
+
+            for variant in file.variants.iter() {
+                inflated.extend(&variant.data);
+            }
+        }
+
+        Ok(inflated)
+    }
+
+    //sizes are stored as u32, so anything larger cannot be bundled.
+    fn size(size: usize, name: impl Fn() -> String) -> Result<u32, UnbundlerError> {
+        u32::try_from(size).map_err(|_| {
+            UnbundlerError::TooLarge(format!(
+                "{} is {} bytes, more than a bundle can hold.",
+                name(),
+                size
+            ))
+        })
+    }
+
+    fn deflate_stream(
+        header: &BundleHeader,
+        inflated: &[u8],
+        block_size: usize,
+    ) -> Result<Vec<u8>, UnbundlerError> {
+        let mut compressed: Vec<u8> = vec![];
+        compressed.extend(header.version.to_le_bytes());
+        compressed
+            .extend(Bundler::size(inflated.len(), || "The bundle".to_string())?.to_le_bytes());
+        compressed.extend(header.reserved.to_le_bytes());
+
+        for chunk in inflated.chunks(block_size) {
+            //the engine inflates whole blocks, so the last one is zero padded.
+            let mut block = chunk.to_vec();
+            block.resize(block_size, 0);
+            Bundler::append_block(&block, &mut compressed)?;
+        }
+
+        Ok(compressed)
+    }
+
+    fn append_block(block: &[u8], buffer: &mut Vec<u8>) -> Result<(), UnbundlerError> {
+        let compressed_block = Bundler::compress_block(block)?;
+
+        //a block length of exactly the block size marks the block as stored uncompressed.
+        if compressed_block.len() >= block.len() {
+            buffer.extend((block.len() as u32).to_le_bytes());
+            buffer.extend(block);
+        } else {
+            buffer.extend((compressed_block.len() as u32).to_le_bytes());
+            buffer.extend(compressed_block);
+        }
+
+        Ok(())
+    }
+
+    fn compress_block(block: &[u8]) -> Result<Vec<u8>, UnbundlerError> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+
+        match encoder.write_all(block) {
+            Ok(_) => {}
+            Err(_) => return Err(UnbundlerError::EncoderWriteAll),
+        }
+
+        match encoder.finish() {
+            Ok(block) => Ok(block),
+            Err(_) => Err(UnbundlerError::EncoderFinish),
+        }
+    }
+}
@@ -1,63 +1,140 @@
-pub struct ByteStream {
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Cursor, Read};
+
+use crate::unbundler::UnbundlerError;
+
+pub struct ByteStream<R: BufRead> {
     position: usize,
-    bytes: Vec<u8>,
+    reader: R,
+    peeked: VecDeque<u8>, //taken from the reader by peek_bytes but not read yet.
+}
+
+impl ByteStream<Cursor<Vec<u8>>> {
+    pub fn new(bytes: Vec<u8>) -> ByteStream<Cursor<Vec<u8>>> {
+        ByteStream::from_reader(Cursor::new(bytes))
+    }
+
+    pub fn remaining_bytes(&self) -> usize {
+        self.reader.get_ref().len() - self.position
+    }
 }
 
-impl ByteStream {
-    pub fn new(bytes: Vec<u8>) -> ByteStream {
-        ByteStream { position: 0, bytes }
+impl<R: BufRead> ByteStream<R> {
+    pub fn from_reader(reader: R) -> ByteStream<R> {
+        ByteStream {
+            position: 0,
+            reader,
+            peeked: VecDeque::new(),
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
     }
-    pub fn read_byte(&mut self) -> u8 {
-        self.position += 1;
-        self.bytes[self.position - 1]
+
+    pub fn at_end(&mut self) -> Result<bool, UnbundlerError> {
+        Ok(self.peeked.is_empty() && self.reader.fill_buf()?.is_empty())
+    }
+
+    pub fn read_byte(&mut self) -> Result<u8, UnbundlerError> {
+        Ok(self.read(1)?[0])
     }
 
-    pub fn read_uint(&mut self) -> u32 {
-        (self.read_byte() as u32)
-            | (self.read_byte() as u32) << 8
-            | (self.read_byte() as u32) << 16
-            | (self.read_byte() as u32) << 24
+    pub fn read_uint(&mut self) -> Result<u32, UnbundlerError> {
+        let bytes = self.read(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
     }
 
-    pub fn read_ulong(&mut self) -> u64 {
-        (self.read_uint() as u64) | (self.read_uint() as u64) << 32
+    pub fn read_ulong(&mut self) -> Result<u64, UnbundlerError> {
+        Ok((self.read_uint()? as u64) | (self.read_uint()? as u64) << 32)
     }
 
-    pub fn read(&mut self, len: usize) -> Vec<u8> {
+    pub fn read(&mut self, len: usize) -> Result<Vec<u8>, UnbundlerError> {
+        let offset = self.position;
         let mut result = vec![];
 
-        for _i in 0..len {
-            result.push(self.read_byte());
+        //take so that a corrupt length does not allocate more than what is left in the stream.
+        self.unread().take(len as u64).read_to_end(&mut result)?;
+        self.position += result.len();
+
+        if result.len() < len {
+            return Err(UnbundlerError::UnexpectedEof {
+                offset,
+                wanted: len,
+            });
         }
 
-        result
+        Ok(result)
     }
 
-    pub fn remaining_bytes(&self) -> usize {
-        self.bytes.len() - self.position
+    //like read, but the bytes are discarded instead of allocated.
+    pub fn skip(&mut self, len: usize) -> Result<(), UnbundlerError> {
+        let offset = self.position;
+        let skipped = io::copy(&mut self.unread().take(len as u64), &mut io::sink())?;
+        self.position += skipped as usize;
+
+        if (skipped as usize) < len {
+            return Err(UnbundlerError::UnexpectedEof {
+                offset,
+                wanted: len,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn read_to_end(&mut self) -> Result<Vec<u8>, UnbundlerError> {
+        let mut result = vec![];
+        self.unread().read_to_end(&mut result)?;
+        self.position += result.len();
+        Ok(result)
+    }
+
+    pub fn peek_byte(&mut self) -> Result<u8, UnbundlerError> {
+        Ok(self.peek_bytes(1)?[0])
     }
 
-    pub fn peek_byte(&mut self) -> u8 {
-        self.bytes[self.position]
+    //takes from the reader until n bytes are peeked, so it is not limited to what the reader has buffered.
+    pub fn peek_bytes(&mut self, n: usize) -> Result<Vec<u8>, UnbundlerError> {
+        while self.peeked.len() < n {
+            let buffer = self.reader.fill_buf()?;
+            if buffer.is_empty() {
+                return Err(UnbundlerError::UnexpectedEof {
+                    offset: self.position,
+                    wanted: n,
+                });
+            }
+
+            let taken = buffer.len().min(n - self.peeked.len());
+            self.peeked.extend(&buffer[..taken]);
+            self.reader.consume(taken);
+        }
+
+        Ok(self.peeked.iter().take(n).copied().collect())
     }
 
-    pub fn peek_bytes(&mut self, n: usize) -> Vec<u8> {
-        self.bytes[self.position..n].to_vec()
+    //the peeked bytes followed by the rest of the reader.
+    fn unread(&mut self) -> impl Read + '_ {
+        (&mut self.peeked).chain(&mut self.reader)
     }
 
-    pub fn read_uleb(&mut self) -> u32 {
+    pub fn read_uleb(&mut self) -> Result<u32, UnbundlerError> {
+        let offset = self.position;
         let mut value: u32 = 0;
         let mut shift: Option<u32> = Some(1);
         loop {
-            let byte = self.read_byte();
+            let byte = self.read_byte()?;
             let data = byte & 127u8;
             let cont = byte & 128u8;
-            value += data as u32 * shift.unwrap();
-            shift = shift.unwrap().checked_mul(128);
+            value = shift
+                .and_then(|shift| (data as u32).checked_mul(shift))
+                .and_then(|data| value.checked_add(data))
+                .ok_or(UnbundlerError::UlebOverflow { offset })?;
+            shift = shift.and_then(|shift| shift.checked_mul(128));
             if cont == 0 {
                 break;
             }
         }
-        value
+        Ok(value)
     }
 }
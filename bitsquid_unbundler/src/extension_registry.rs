@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use crate::unbundler::UnbundlerError;
+
+pub const TEXTURE: u64 = 0xcd4238c6a0c69e32;
+pub const PACKAGE: u64 = 0xad9c6d9ed1e5e77a;
+pub const STRINGS: u64 = 0x0d972bab10b40fd3;
+
+const EMBEDDED: &str = include_str!("extensions.txt");
+
+//maps extension hashes to names. starts from the embedded table and can be extended from files,
+//so newly discovered types can be shared without rebuilding.
+#[derive(Default)]
+pub struct ExtensionRegistry {
+    names: RwLock<BTreeMap<u64, String>>,
+    unknown: Mutex<BTreeSet<u64>>, //hashes looked up without a name, in this run.
+}
+
+impl ExtensionRegistry {
+    pub fn embedded() -> ExtensionRegistry {
+        let registry = ExtensionRegistry::default();
+        registry
+            .parse(EMBEDDED)
+            .expect("The embedded extension table is invalid.");
+        registry
+    }
+
+    //the registry every bundle is read with.
+    pub fn global() -> &'static ExtensionRegistry {
+        static GLOBAL: OnceLock<ExtensionRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(ExtensionRegistry::embedded)
+    }
+
+    //adds every "0x<hash> <name>" line and returns how many were read.
+    //blank lines and lines starting with # are ignored.
+    //later names replace earlier ones, so a user file can rename built in extensions.
+    pub fn parse(&self, text: &str) -> Result<usize, UnbundlerError> {
+        let mut names = BTreeMap::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let hash = parts.next().unwrap_or_default();
+            let hash = u64::from_str_radix(hash.trim_start_matches("0x"), 16).map_err(|_| {
+                UnbundlerError::InvalidHash(format!("line {}: {}", index + 1, line))
+            })?;
+
+            //hashes without a name, as written by unknown_table, are skipped.
+            if let Some(name) = parts.next() {
+                names.insert(hash, name.to_string());
+            }
+        }
+
+        let added = names.len();
+        self.names.write().unwrap().extend(names);
+        Ok(added)
+    }
+
+    pub fn load(&self, file_path: &Path) -> Result<usize, UnbundlerError> {
+        self.parse(&fs::read_to_string(file_path)?)
+    }
+
+    //unknown extensions are written as their hex hash, like in the unknown table, which hash() reads back.
+    pub fn name(&self, hashed_name: u64) -> String {
+        if let Some(name) = self.names.read().unwrap().get(&hashed_name) {
+            return name.clone();
+        }
+
+        self.unknown.lock().unwrap().insert(hashed_name);
+        format!("{:#018x}", hashed_name)
+    }
+
+    //inverse of name.
+    pub fn hash(&self, extension: &str) -> Option<u64> {
+        let names = self.names.read().unwrap();
+        match names.iter().find(|(_hash, name)| *name == extension) {
+            Some((hash, _name)) => Some(*hash),
+            None => extension
+                .strip_prefix("0x")
+                .and_then(|hash| u64::from_str_radix(hash, 16).ok()),
+        }
+    }
+
+    pub fn unknown(&self) -> Vec<u64> {
+        self.unknown.lock().unwrap().iter().copied().collect()
+    }
+
+    //the unknown hashes in the format parse reads, ready to have names filled in and be shared.
+    pub fn unknown_table(&self) -> String {
+        let mut table =
+            String::from("# extension hashes without a name. add a name after each hash.\n");
+        for hash in self.unknown() {
+            table.push_str(&format!("{:#018x}\n", hash));
+        }
+        table
+    }
+}
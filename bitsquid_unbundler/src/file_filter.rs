@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use murmur32_gen::dictionary::Dictionary;
+
+use crate::extension_registry::ExtensionRegistry;
+use crate::game_profile::GameProfile;
+use crate::unbundler::UnbundlerError;
+
+//selects which files are read out of a bundle. an empty list does not filter anything.
+#[derive(Clone, Default)]
+pub struct FileFilter {
+    pub extensions: Vec<u64>,
+    pub paths: Vec<u64>,
+    pub names: Vec<String>, //globs over the file name. * stays within a directory, ** does not.
+    pub dictionary: Option<Arc<Dictionary>>, //restores the names the globs are matched against.
+    pub profile: Option<GameProfile>, //the --game profile, whose extension names the globs see.
+}
+
+impl FileFilter {
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty() && self.paths.is_empty() && self.names.is_empty()
+    }
+
+    pub fn matches(&self, extension: u64, path: u64) -> bool {
+        if !self.extensions.is_empty() && !self.extensions.contains(&extension) {
+            return false;
+        }
+
+        if !self.paths.is_empty() && !self.paths.contains(&path) {
+            return false;
+        }
+
+        if !self.names.is_empty() {
+            let name = self.name(extension, path);
+            return self
+                .names
+                .iter()
+                .any(|glob| FileFilter::glob_matches(glob.as_bytes(), name.as_bytes()));
+        }
+
+        true
+    }
+
+    //comma separated extensions such as "lua,strings", named as the profile names them.
+    pub fn parse_extensions(
+        extensions: &str,
+        profile: Option<&GameProfile>,
+    ) -> Result<Vec<u64>, UnbundlerError> {
+        FileFilter::split(extensions)
+            .map(|extension| {
+                match profile {
+                    Some(profile) => profile.extension_hash(extension),
+                    None => ExtensionRegistry::global().hash(extension),
+                }
+                .ok_or_else(|| UnbundlerError::Extension(extension.to_string()))
+            })
+            .collect()
+    }
+
+    //comma separated path hashes such as "0x1234,0xabcd".
+    pub fn parse_paths(paths: &str) -> Result<Vec<u64>, UnbundlerError> {
+        FileFilter::split(paths)
+            .map(|path| {
+                u64::from_str_radix(path.trim_start_matches("0x"), 16)
+                    .map_err(|_| UnbundlerError::InvalidHash(path.to_string()))
+            })
+            .collect()
+    }
+
+    fn split(list: &str) -> impl Iterator<Item = &str> {
+        list.split(',')
+            .map(|item| item.trim())
+            .filter(|item| !item.is_empty())
+    }
+
+    //the name a file is written with: its restored name if the dictionary has it, or else its hex name.
+    fn name(&self, extension: u64, path: u64) -> String {
+        let extension = match &self.profile {
+            Some(profile) => profile.extension(extension),
+            None => ExtensionRegistry::global().name(extension),
+        };
+        match self
+            .dictionary
+            .as_ref()
+            .and_then(|dictionary| dictionary.lookup(path))
+        {
+            Some(name) => format!("{}.{}", name, extension),
+            None => format!("{:#x}.{}", path, extension),
+        }
+    }
+
+    fn glob_matches(glob: &[u8], name: &[u8]) -> bool {
+        match glob.split_first() {
+            None => name.is_empty(),
+            Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+                let rest = &rest[1..];
+                //"**/" can also match no directories at all.
+                let no_directories = rest
+                    .strip_prefix(b"/")
+                    .is_some_and(|rest| FileFilter::glob_matches(rest, name));
+                no_directories
+                    || (0..=name.len()).any(|i| FileFilter::glob_matches(rest, &name[i..]))
+            }
+            Some((b'*', rest)) => {
+                for i in 0..=name.len() {
+                    if FileFilter::glob_matches(rest, &name[i..]) {
+                        return true;
+                    }
+                    if i < name.len() && name[i] == b'/' {
+                        break;
+                    }
+                }
+                false
+            }
+            Some((b'?', rest)) => {
+                matches!(name.split_first(), Some((c, name)) if *c != b'/' && FileFilter::glob_matches(rest, name))
+            }
+            Some((c, rest)) => {
+                matches!(name.split_first(), Some((n, name)) if n == c && FileFilter::glob_matches(rest, name))
+            }
+        }
+    }
+}
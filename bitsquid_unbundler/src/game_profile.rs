@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+use crate::bundle_header::DEFAULT_VERSION;
+use crate::extension_registry::ExtensionRegistry;
+use crate::inflater::BLOCK_SIZE;
+use crate::unbundler::UnbundlerError;
+
+//the parts of the bundle format which differ between bitsquid titles.
+//profiles are built in or loaded from a json or toml file. missing fields keep their default.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct GameProfile {
+    pub name: String,
+    #[serde(deserialize_with = "from_numbers")]
+    pub versions: Vec<u32>, //bundle header versions which select this profile when none is given.
+    pub block_size: usize,
+    pub checksum_size: usize, //bytes between the file count and the name table.
+    pub name_entry_size: usize, //bytes per name table entry. only the extension and path are read.
+    pub skipped_extensions: Vec<String>, //files in a bundle directory which are not bundles.
+    pub streams: bool,        //whether bundles keep streamed data in a .stream file beside them.
+    #[serde(deserialize_with = "from_hex_keys")]
+    pub extensions: BTreeMap<u64, String>, //looked up before the extension registry.
+    pub verified: bool,       //whether the layout was checked against the game's own bundles.
+}
+
+impl Default for GameProfile {
+    fn default() -> GameProfile {
+        GameProfile {
+            name: "mww".to_string(),
+            versions: vec![DEFAULT_VERSION],
+            block_size: BLOCK_SIZE,
+            checksum_size: 256,
+            name_entry_size: 16,
+            skipped_extensions: vec!["stream".to_string(), "ini".to_string(), "data".to_string()],
+            streams: true,
+            extensions: BTreeMap::new(),
+            verified: true,
+        }
+    }
+}
+
+impl GameProfile {
+    //only mww has been checked against the game's own bundles. the others are guesses which
+    //are unverified until someone reads that game with them.
+    pub fn builtin() -> Vec<GameProfile> {
+        let unverified = |name: &str| GameProfile {
+            name: name.to_string(),
+            versions: vec![],
+            verified: false,
+            ..GameProfile::default()
+        };
+
+        vec![
+            GameProfile::default(),
+            GameProfile {
+                versions: vec![0xf0000005, 0xf0000006],
+                name_entry_size: 24,
+                ..unverified("vermintide2")
+            },
+            //these are read with the mww layout. they are not detected by version, only named with --game.
+            unverified("magicka2"),
+            unverified("vermintide"),
+            unverified("helldivers"),
+            unverified("wotr"),
+        ]
+    }
+
+    //a built in profile by name, or else a path to a .json or .toml profile.
+    pub fn named(name: &str) -> Result<GameProfile, UnbundlerError> {
+        match GameProfile::builtin()
+            .into_iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+        {
+            Some(profile) => Ok(profile),
+            None if Path::new(name).is_file() => GameProfile::load(Path::new(name)),
+            None => Err(UnbundlerError::Profile(format!(
+                "{} is neither a built in profile nor a profile file. The built in profiles are: {}",
+                name,
+                GameProfile::builtin()
+                    .iter()
+                    .map(|profile| profile.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+
+    pub fn load(profile_path: &Path) -> Result<GameProfile, UnbundlerError> {
+        let text = fs::read_to_string(profile_path)?;
+        let invalid = |e: String| UnbundlerError::Profile(format!("{:?}: {}", profile_path, e));
+
+        match profile_path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(|e| invalid(e.to_string())),
+            _ => serde_json::from_str(&text).map_err(|e| invalid(e.to_string())),
+        }
+    }
+
+    //the built in profile for a bundle header version. unknown versions are read like mww bundles.
+    pub fn detect(version: u32) -> GameProfile {
+        GameProfile::builtin()
+            .into_iter()
+            .find(|profile| profile.versions.contains(&version))
+            .unwrap_or_default()
+    }
+
+    pub fn extension(&self, hashed_name: u64) -> String {
+        match self.extensions.get(&hashed_name) {
+            Some(extension) => extension.clone(),
+            None => ExtensionRegistry::global().name(hashed_name),
+        }
+    }
+
+    //inverse of extension.
+    pub fn extension_hash(&self, extension: &str) -> Option<u64> {
+        match self
+            .extensions
+            .iter()
+            .find(|(_hash, name)| *name == extension)
+        {
+            Some((hash, _name)) => Some(*hash),
+            None => ExtensionRegistry::global().hash(extension),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Integer(u32),
+    Hex(String),
+}
+
+//versions may be written as numbers or as hex strings such as "0xf0000004".
+fn from_numbers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u32>, D::Error> {
+    Vec::<Number>::deserialize(deserializer)?
+        .into_iter()
+        .map(|number| match number {
+            Number::Integer(number) => Ok(number),
+            Number::Hex(hex) => {
+                u32::from_str_radix(hex.trim_start_matches("0x"), 16).map_err(D::Error::custom)
+            }
+        })
+        .collect()
+}
+
+//extension hashes are written like the unbundler writes them, e.g. "0xa14e8dfa2cd117e2" = "lua".
+fn from_hex_keys<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<u64, String>, D::Error> {
+    BTreeMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(hash, extension)| {
+            u64::from_str_radix(hash.trim_start_matches("0x"), 16)
+                .map(|hash| (hash, extension))
+                .map_err(D::Error::custom)
+        })
+        .collect()
+}
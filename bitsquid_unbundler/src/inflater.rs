@@ -0,0 +1,96 @@
+use std::io::{self, BufRead, Read, Write};
+
+use flate2::write::ZlibDecoder;
+
+use crate::byte_stream::ByteStream;
+use crate::unbundler::UnbundlerError;
+
+pub const BLOCK_SIZE: usize = 1 << 16;
+
+//inflates the zlib blocks of a bundle one at a time, so only a single block is ever held in memory.
+pub struct Inflater<R: BufRead> {
+    compressed_stream: ByteStream<R>,
+    block: Vec<u8>,
+    position: usize,
+    inflated_size: usize,
+    block_size: usize, //blocks of exactly this length are stored uncompressed.
+}
+
+impl<R: BufRead> Inflater<R> {
+    pub fn new(compressed_stream: ByteStream<R>, block_size: usize) -> Inflater<R> {
+        Inflater {
+            compressed_stream,
+            block: vec![],
+            position: 0,
+            inflated_size: 0,
+            block_size,
+        }
+    }
+
+    //total number of bytes inflated so far.
+    pub fn inflated_size(&self) -> usize {
+        self.inflated_size
+    }
+
+    //returns false once there are no blocks left in the compressed stream.
+    pub fn next_block(&mut self) -> Result<bool, UnbundlerError> {
+        if self.compressed_stream.at_end()? {
+            return Ok(false);
+        }
+
+        let len = self.compressed_stream.read_uint()? as usize;
+
+        self.block = if len == self.block_size {
+            self.compressed_stream.read(len)?
+        } else {
+            let compressed_block = self.compressed_stream.read(len)?;
+            Inflater::<R>::decompress_block(&compressed_block)?
+        };
+        self.position = 0;
+        self.inflated_size += self.block.len();
+
+        Ok(true)
+    }
+
+    fn decompress_block(compressed_block: &[u8]) -> Result<Vec<u8>, UnbundlerError> {
+        let mut decoder = ZlibDecoder::new(vec![]);
+
+        match decoder.write_all(compressed_block) {
+            Ok(_) => {}
+            Err(_) => return Err(UnbundlerError::DecoderWriteAll),
+        }
+
+        match decoder.finish() {
+            Ok(block) => Ok(block),
+            Err(_) => Err(UnbundlerError::DecoderFinish),
+        }
+    }
+}
+
+impl<R: BufRead> Read for Inflater<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for Inflater<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.position == self.block.len() {
+            match self.next_block() {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            }
+        }
+
+        Ok(&self.block[self.position..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position = (self.position + amt).min(self.block.len());
+    }
+}
@@ -1,5 +1,21 @@
-mod byte_stream;
-mod extensions;
+pub mod bcn;
+pub mod bundle_diff;
+pub mod bundle_header;
+pub mod bundle_index;
+pub mod bundle_manifest;
+pub mod bundle_reader;
+pub mod bundler;
+pub mod byte_stream;
+pub mod extension_registry;
+pub mod file_filter;
+pub mod game_profile;
+pub mod inflater;
+pub mod name_harvester;
+pub mod package;
+pub mod resource_index;
+pub mod strings;
+pub mod texture;
 pub mod unbundled_directory;
 pub mod unbundled_file;
 pub mod unbundler;
+pub mod verifier;
@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+
+use murmur32_gen::murmur::hash64;
+use serde::Serialize;
+
+use crate::bundle_index::as_hex;
+use crate::extension_registry::ExtensionRegistry;
+use crate::file_filter::FileFilter;
+use crate::unbundler::{Unbundler, UnbundlerError};
+
+const MIN_STRING_LEN: usize = 4;
+
+//a string found inside a resource which hashes to the name of a resource in the bundles.
+#[derive(Serialize, Clone)]
+pub struct HarvestedName {
+    #[serde(serialize_with = "as_hex")]
+    pub path: u64,
+    pub name: String,
+    pub extensions: Vec<String>, //the types of the resources with this name.
+}
+
+//finds resource names in the data of other resources: package files listing their members,
+//lua scripts calling require "scripts/..." and units, materials and levels referencing paths.
+//luajit stores its constant strings as they are, so compiled scripts are searched the same way.
+pub struct NameHarvester {
+    names: HashMap<u64, BTreeSet<String>>, //the name hashes in the bundles and their types.
+}
+
+impl NameHarvester {
+    //every resource in the bundles is a target. the unbundler's filter only picks the files harvest() searches.
+    pub fn new(unbundler: &Unbundler) -> Result<NameHarvester, UnbundlerError> {
+        let all_files = Unbundler {
+            file_path: unbundler.file_path.clone(),
+            filter: FileFilter::default(),
+            profile: unbundler.profile.clone(),
+        };
+
+        let mut names = HashMap::<u64, BTreeSet<String>>::new();
+        for index in all_files.index()? {
+            for entry in index.entries {
+                names.entry(entry.path).or_default().insert(entry.extension);
+            }
+        }
+
+        Ok(NameHarvester { names })
+    }
+
+    //every name hit by a string in any resource, sorted by hash.
+    pub fn harvest(
+        &self,
+        unbundler: &Unbundler,
+        jobs: usize,
+    ) -> Result<Vec<HarvestedName>, UnbundlerError> {
+        let found = Mutex::new(BTreeMap::<u64, String>::new());
+
+        unbundler.for_each_file_parallel(jobs, |_bundle_name, file| {
+            let mut hits = vec![];
+            for variant in file.variants.iter() {
+                for string in NameHarvester::strings(&variant.data) {
+                    hits.extend(self.hits(string));
+                }
+            }
+
+            if !hits.is_empty() {
+                found.lock().unwrap().extend(hits);
+            }
+        })?;
+
+        let found = found.into_inner().unwrap();
+        Ok(found
+            .into_iter()
+            .map(|(path, name)| HarvestedName {
+                path,
+                name,
+                extensions: self.names[&path].iter().cloned().collect(),
+            })
+            .collect())
+    }
+
+    fn hits(&self, string: &str) -> Vec<(u64, String)> {
+        NameHarvester::candidates(string)
+            .into_iter()
+            .map(|candidate| (hash64(&candidate), candidate))
+            .filter(|(hash, _candidate)| self.names.contains_key(hash))
+            .collect()
+    }
+
+    //runs of characters which may appear in a resource name. quotes, spaces, brackets
+    //and anything else end a run, so "Unit.spawn("units/x")" gives Unit.spawn and units/x.
+    pub fn strings(data: &[u8]) -> impl Iterator<Item = &str> {
+        data.split(|byte| !NameHarvester::is_name_byte(*byte))
+            .filter(|run| run.len() >= MIN_STRING_LEN)
+            .map(|run| std::str::from_utf8(run).unwrap())
+    }
+
+    fn is_name_byte(byte: u8) -> bool {
+        byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'/' | b'\\' | b'.' | b'-')
+    }
+
+    //the names a string could be. names are written with / and without their extension,
+    //but strings may use \, end in any known extension or be a dotted lua module name.
+    pub fn candidates(string: &str) -> BTreeSet<String> {
+        let string = string.replace('\\', "/");
+        let string = string.trim_start_matches("./").trim_start_matches('/');
+
+        let mut candidates = BTreeSet::new();
+        for candidate in [string.to_string(), string.to_lowercase()] {
+            if let Some((name, extension)) = candidate.rsplit_once('.') {
+                if ExtensionRegistry::global().hash(extension).is_some() {
+                    candidates.insert(name.to_string());
+                }
+                if !candidate.contains('/') {
+                    candidates.insert(candidate.replace('.', "/"));
+                }
+            }
+            candidates.insert(candidate);
+        }
+
+        candidates.retain(|candidate| !candidate.is_empty());
+        candidates
+    }
+}
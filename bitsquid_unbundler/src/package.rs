@@ -0,0 +1,164 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use murmur32_gen::dictionary::Dictionary;
+use serde::Serialize;
+
+use crate::bundle_index::as_hex;
+use crate::byte_stream::ByteStream;
+use crate::extension_registry::{ExtensionRegistry, PACKAGE};
+use crate::file_filter::FileFilter;
+use crate::unbundler::{Unbundler, UnbundlerError};
+
+//a package resource: the resources the engine loads together when the package is loaded.
+//version u32, count u32, then count (extension u64, path u64) pairs. anything after the
+//pairs, such as the flags of newer versions, is kept as it is so the package can be written back.
+#[derive(Clone)]
+pub struct Package {
+    pub version: u32,
+    pub entries: Vec<(u64, u64)>, //(extension, path)
+    pub trailer: Vec<u8>,
+}
+
+impl Package {
+    pub fn parse(data: &[u8]) -> Result<Package, UnbundlerError> {
+        let mut stream = ByteStream::new(data.to_vec());
+        let version = stream.read_uint()?;
+        let count = stream.read_uint()?;
+
+        let mut entries = vec![];
+        for _ in 0..count {
+            entries.push((stream.read_ulong()?, stream.read_ulong()?));
+        }
+
+        let trailer = stream.read(stream.remaining_bytes())?;
+        Ok(Package {
+            version,
+            entries,
+            trailer,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(self.version.to_le_bytes());
+        bytes.extend((self.entries.len() as u32).to_le_bytes());
+        for (extension, path) in self.entries.iter() {
+            bytes.extend(extension.to_le_bytes());
+            bytes.extend(path.to_le_bytes());
+        }
+        bytes.extend(&self.trailer);
+        bytes
+    }
+
+    //one "<extension> <name>" line per entry. unknown names are written as hex.
+    pub fn to_text(&self, names: &Dictionary) -> String {
+        let mut text = String::new();
+        for (extension, path) in self.entries.iter() {
+            text.push_str(&Package::entry_name(*extension, *path, names));
+            text.push('\n');
+        }
+        text
+    }
+
+    fn entry_name(extension: u64, path: u64, names: &Dictionary) -> String {
+        let name = match names.lookup(path) {
+            Some(name) => name.to_string(),
+            None => format!("{:#x}", path),
+        };
+        format!("{} {}", ExtensionRegistry::global().name(extension), name)
+    }
+}
+
+//which bundles every package needs, found by looking up each entry in the name tables.
+#[derive(Serialize)]
+pub struct PackageGraph {
+    pub packages: Vec<PackageNode>,
+}
+
+#[derive(Serialize)]
+pub struct PackageNode {
+    #[serde(serialize_with = "as_hex")]
+    pub path: u64,
+    pub name: Option<String>,
+    pub bundles: Vec<String>, //the bundles the package itself is in.
+    pub entries: Vec<String>, //"<extension> <name>" as written by Package::to_text.
+    pub dependencies: BTreeMap<String, usize>, //bundle to how many of the entries it contains.
+    pub missing: Vec<String>, //entries which are in none of the bundles, e.g. streamed resources.
+}
+
+impl PackageGraph {
+    //the unbundler's filter selects which packages are read. every bundle is searched for their entries.
+    pub fn build(
+        unbundler: &Unbundler,
+        names: &Dictionary,
+        jobs: usize,
+    ) -> Result<PackageGraph, UnbundlerError> {
+        let mut locations = HashMap::<(u64, u64), Vec<String>>::new();
+        let mut all = unbundler.clone();
+        all.filter = FileFilter::default();
+        for index in all.index()? {
+            for entry in index.entries {
+                locations
+                    .entry((entry.extension_hash, entry.path))
+                    .or_default()
+                    .push(index.bundle_name.clone());
+            }
+        }
+
+        let mut packages_only = unbundler.clone();
+        packages_only.filter.extensions = vec![PACKAGE];
+        let packages = Mutex::new(BTreeMap::<u64, (Vec<String>, Package)>::new());
+
+        //a package which cannot be parsed is reported and skipped, like a bad bundle.
+        packages_only.for_each_file_parallel(jobs, |bundle_name, file| {
+            let Some(variant) = file.variants.first() else {
+                return;
+            };
+            match Package::parse(&variant.data) {
+                Ok(package) => {
+                    packages
+                        .lock()
+                        .unwrap()
+                        .entry(file.path)
+                        .or_insert_with(|| (vec![], package))
+                        .0
+                        .push(bundle_name.to_string());
+                }
+                Err(e) => eprintln!(
+                    "Skipping package {:#x} in {} which could not be parsed: {}",
+                    file.path, bundle_name, e
+                ),
+            }
+        })?;
+
+        let mut nodes = vec![];
+        for (path, (mut bundles, package)) in packages.into_inner().unwrap() {
+            bundles.sort();
+
+            let mut dependencies = BTreeMap::new();
+            let mut missing = vec![];
+            for (extension, entry_path) in package.entries.iter() {
+                match locations.get(&(*extension, *entry_path)) {
+                    Some(entry_bundles) => {
+                        for bundle in entry_bundles {
+                            *dependencies.entry(bundle.clone()).or_insert(0) += 1;
+                        }
+                    }
+                    None => missing.push(Package::entry_name(*extension, *entry_path, names)),
+                }
+            }
+
+            nodes.push(PackageNode {
+                path,
+                name: names.lookup(path).map(String::from),
+                bundles,
+                entries: package.to_text(names).lines().map(String::from).collect(),
+                dependencies,
+                missing,
+            });
+        }
+
+        Ok(PackageGraph { packages: nodes })
+    }
+}
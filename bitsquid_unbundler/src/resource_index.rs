@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bundle_index::{as_hex, from_hex};
+use crate::file_filter::FileFilter;
+use crate::unbundled_file::UnbundledFile;
+use crate::unbundler::{Unbundler, UnbundlerError};
+
+//maps every resource to the bundles which contain a copy of it.
+//it is saved to disk so that it can be queried without reading the bundles again.
+#[derive(Serialize, Deserialize)]
+pub struct ResourceIndex {
+    pub resources: Vec<ResourceLocation>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ResourceLocation {
+    #[serde(serialize_with = "as_hex", deserialize_with = "from_hex")]
+    pub extension_hash: u64,
+    pub extension: String,
+    #[serde(serialize_with = "as_hex", deserialize_with = "from_hex")]
+    pub path: u64,
+    pub copies: Vec<ResourceCopy>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ResourceCopy {
+    pub bundle_name: String,
+    #[serde(serialize_with = "as_hex", deserialize_with = "from_hex")]
+    pub digest: u64,
+    pub size: u64, //sum of the sizes of every variant.
+}
+
+impl ResourceIndex {
+    pub fn build(unbundler: &Unbundler, jobs: usize) -> Result<ResourceIndex, UnbundlerError> {
+        let resources = Mutex::new(BTreeMap::<(u64, u64), ResourceLocation>::new());
+
+        unbundler.for_each_file_parallel(jobs, |bundle_name, file| {
+            let copy = ResourceCopy {
+                bundle_name: bundle_name.to_string(),
+                digest: ResourceIndex::digest(&file),
+                size: file.variants.iter().map(|v| v.data.len() as u64).sum(),
+            };
+
+            resources
+                .lock()
+                .unwrap()
+                .entry((file.header.extension, file.path))
+                .or_insert_with(|| ResourceLocation {
+                    extension_hash: file.header.extension,
+                    extension: file.extension.clone(),
+                    path: file.path,
+                    copies: vec![],
+                })
+                .copies
+                .push(copy);
+        })?;
+
+        let mut resources: Vec<ResourceLocation> =
+            resources.into_inner().unwrap().into_values().collect();
+
+        //bundles are visited in any order by the worker threads.
+        for resource in resources.iter_mut() {
+            resource
+                .copies
+                .sort_by(|a, b| a.bundle_name.cmp(&b.bundle_name));
+        }
+
+        Ok(ResourceIndex { resources })
+    }
+
+    pub fn load(index_path: &Path) -> Result<ResourceIndex, UnbundlerError> {
+        let json = fs::read_to_string(index_path)?;
+        serde_json::from_str(&json).map_err(|e| UnbundlerError::Json(e.to_string()))
+    }
+
+    pub fn save(&self, index_path: &Path) -> Result<(), UnbundlerError> {
+        let json = serde_json::to_string(self).map_err(|e| UnbundlerError::Json(e.to_string()))?;
+        fs::write(index_path, json)?;
+        Ok(())
+    }
+
+    pub fn select(&self, filter: &FileFilter) -> Vec<&ResourceLocation> {
+        self.resources
+            .iter()
+            .filter(|resource| filter.matches(resource.extension_hash, resource.path))
+            .collect()
+    }
+
+    //resources with at least two copies which do not have the same bytes.
+    pub fn mismatched(&self) -> Vec<&ResourceLocation> {
+        self.resources
+            .iter()
+            .filter(|resource| !resource.identical())
+            .collect()
+    }
+
+    //fnv-1a over the language, data and streamed data of every variant.
+    //it only has to tell copies apart, not resist tampering.
+    fn digest(file: &UnbundledFile) -> u64 {
+        let mut digest: u64 = 0xcbf29ce484222325;
+
+        for variant in file.variants.iter() {
+            for byte in variant
+                .language
+                .to_le_bytes()
+                .iter()
+                .chain(variant.data.iter())
+                .chain(variant.stream.iter())
+            {
+                digest ^= *byte as u64;
+                digest = digest.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        digest
+    }
+}
+
+impl ResourceLocation {
+    pub fn identical(&self) -> bool {
+        self.copies
+            .windows(2)
+            .all(|pair| pair[0].digest == pair[1].digest)
+    }
+}
@@ -0,0 +1,237 @@
+use murmur32_gen::dictionary::Dictionary;
+use murmur32_gen::murmur::hash32;
+use serde::{Deserialize, Serialize};
+
+use crate::byte_stream::ByteStream;
+use crate::unbundler::UnbundlerError;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum StringsFormat {
+    Csv,
+    Json,
+}
+
+impl StringsFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            StringsFormat::Csv => "csv",
+            StringsFormat::Json => "json",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<StringsFormat> {
+        match extension {
+            "csv" => Some(StringsFormat::Csv),
+            "json" => Some(StringsFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+//one row of an exported table. the key is the hex murmur32 hash of the name. when a row
+//has a name but no key, the key is hashed from the name, so new strings can be added by name.
+#[derive(Serialize, Deserialize)]
+pub struct StringEntry {
+    #[serde(default)]
+    pub key: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub value: String,
+}
+
+//a strings resource: the localized text of one language, looked up by the murmur32 hash of its key.
+//version u32, count u32, then count (key u32, offset u32) pairs and the null terminated utf8
+//values. offsets are from the start of the values.
+#[derive(Clone)]
+pub struct StringTable {
+    pub version: u32,
+    pub entries: Vec<(u32, String)>, //(key, value)
+}
+
+impl StringTable {
+    pub fn parse(data: &[u8]) -> Result<StringTable, UnbundlerError> {
+        let mut stream = ByteStream::new(data.to_vec());
+        let version = stream.read_uint()?;
+        let count = stream.read_uint()?;
+
+        let mut keys = vec![];
+        for _ in 0..count {
+            keys.push((stream.read_uint()?, stream.read_uint()? as usize));
+        }
+        let values = stream.read(stream.remaining_bytes())?;
+
+        let mut entries = vec![];
+        for (key, offset) in keys {
+            let value = values
+                .get(offset..)
+                .and_then(|value| value.split(|byte| *byte == 0).next())
+                .ok_or_else(|| {
+                    UnbundlerError::Strings(format!(
+                        "the value of {:#010x} is outside the table",
+                        key
+                    ))
+                })?;
+            let value = String::from_utf8(value.to_vec()).map_err(|_| {
+                UnbundlerError::Strings(format!("the value of {:#010x} is not utf8", key))
+            })?;
+            entries.push((key, value));
+        }
+
+        Ok(StringTable { version, entries })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(self.version.to_le_bytes());
+        bytes.extend((self.entries.len() as u32).to_le_bytes());
+
+        let mut values = vec![];
+        for (key, value) in self.entries.iter() {
+            bytes.extend(key.to_le_bytes());
+            bytes.extend((values.len() as u32).to_le_bytes());
+            values.extend(value.as_bytes());
+            values.push(0);
+        }
+        bytes.extend(values);
+        bytes
+    }
+
+    //names are resolved with the dictionary. unknown names are left empty.
+    pub fn to_rows(&self, names: &Dictionary) -> Vec<StringEntry> {
+        self.entries
+            .iter()
+            .map(|(key, value)| StringEntry {
+                key: format!("{:#010x}", key),
+                name: names.lookup32(*key).map(String::from),
+                value: value.clone(),
+            })
+            .collect()
+    }
+
+    //an edited export becomes the entries of the table. the version is kept.
+    pub fn set_rows(&mut self, rows: Vec<StringEntry>) -> Result<(), UnbundlerError> {
+        let mut entries = vec![];
+        for row in rows {
+            let name = row.name.filter(|name| !name.is_empty());
+            let key = match (row.key.is_empty(), name) {
+                (false, _) => u32::from_str_radix(row.key.trim_start_matches("0x"), 16)
+                    .map_err(|_| UnbundlerError::Strings(format!("invalid key {}", row.key)))?,
+                (true, Some(name)) => hash32(&name),
+                (true, None) => {
+                    return Err(UnbundlerError::Strings(format!(
+                        "the row \"{}\" has neither a key nor a name",
+                        row.value
+                    )))
+                }
+            };
+            entries.push((key, row.value));
+        }
+
+        self.entries = entries;
+        Ok(())
+    }
+
+    pub fn export(&self, format: StringsFormat, names: &Dictionary) -> String {
+        let rows = self.to_rows(names);
+        match format {
+            StringsFormat::Json => serde_json::to_string_pretty(&rows).unwrap(),
+            StringsFormat::Csv => {
+                let mut csv = String::from("key,name,value\n");
+                for row in rows {
+                    let name = row.name.unwrap_or_default();
+                    for (index, field) in [&row.key, &name, &row.value].into_iter().enumerate() {
+                        if index > 0 {
+                            csv.push(',');
+                        }
+                        csv.push_str(&StringTable::csv_field(field));
+                    }
+                    csv.push('\n');
+                }
+                csv
+            }
+        }
+    }
+
+    pub fn import(&mut self, format: StringsFormat, text: &str) -> Result<(), UnbundlerError> {
+        let rows = match format {
+            StringsFormat::Json => {
+                serde_json::from_str(text).map_err(|e| UnbundlerError::Json(format!("{}", e)))?
+            }
+            StringsFormat::Csv => StringTable::parse_csv(text)?,
+        };
+        self.set_rows(rows)
+    }
+
+    //fields with commas, quotes or line breaks are quoted, with quotes doubled.
+    fn csv_field(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+
+    //the header row names the columns, so they can be in any order.
+    fn parse_csv(text: &str) -> Result<Vec<StringEntry>, UnbundlerError> {
+        let mut records = StringTable::csv_records(text)?.into_iter();
+        let header = records.next().unwrap_or_default();
+        let column = |name: &str| header.iter().position(|column| column.trim() == name);
+        let (key, name) = (column("key"), column("name"));
+        let value = column("value")
+            .ok_or_else(|| UnbundlerError::Strings("the csv has no value column".to_string()))?;
+
+        Ok(records
+            .filter(|record| record.iter().any(|field| !field.is_empty()))
+            .map(|mut record| {
+                let mut take = |index: Option<usize>| {
+                    index
+                        .and_then(|index| record.get_mut(index))
+                        .map(std::mem::take)
+                        .unwrap_or_default()
+                };
+                StringEntry {
+                    key: take(key),
+                    name: Some(take(name)),
+                    value: take(Some(value)),
+                }
+            })
+            .collect())
+    }
+
+    fn csv_records(text: &str) -> Result<Vec<Vec<String>>, UnbundlerError> {
+        let mut records = vec![];
+        let mut record = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' if quoted => quoted = false,
+                '"' if field.is_empty() => quoted = true,
+                ',' if !quoted => record.push(std::mem::take(&mut field)),
+                '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+                '\n' if !quoted => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                c => field.push(c),
+            }
+        }
+
+        if quoted {
+            return Err(UnbundlerError::Strings(
+                "the csv ends inside a quoted field".to_string(),
+            ));
+        }
+        if !field.is_empty() || !record.is_empty() {
+            record.push(field);
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
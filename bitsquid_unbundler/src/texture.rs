@@ -0,0 +1,706 @@
+use crate::bcn;
+use crate::byte_stream::ByteStream;
+use crate::unbundler::UnbundlerError;
+
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+const HEADER_SIZE: u32 = 124;
+const PIXEL_FORMAT_SIZE: u32 = 32;
+
+//the largest textures direct3d 11 allows. anything beyond them is a corrupt header.
+const MAX_DIMENSION: u32 = 16384;
+const MAX_DEPTH: u32 = 2048;
+const MAX_MIP_COUNT: u32 = 32;
+
+//dwFlags
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+const DDSD_DEPTH: u32 = 0x800000;
+
+//ddspf.dwFlags
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_ALPHA: u32 = 0x2;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x20000;
+
+//dwCaps and dwCaps2
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+const DDSCAPS2_CUBEMAP_ALL_FACES: u32 = 0xfe00;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+
+//DDS_HEADER_DXT10
+const DIMENSION_TEXTURE2D: u32 = 3;
+const DIMENSION_TEXTURE3D: u32 = 4;
+const MISC_TEXTURECUBE: u32 = 0x4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextureFormat {
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc6h,
+    Bc7,
+    Rgba8,
+    Bgra8,
+    R8,
+}
+
+impl TextureFormat {
+    pub fn dxgi_format(self) -> u32 {
+        match self {
+            TextureFormat::Bc1 => 71,
+            TextureFormat::Bc2 => 74,
+            TextureFormat::Bc3 => 77,
+            TextureFormat::Bc4 => 80,
+            TextureFormat::Bc5 => 83,
+            TextureFormat::Bc6h => 95,
+            TextureFormat::Bc7 => 98,
+            TextureFormat::Rgba8 => 28,
+            TextureFormat::Bgra8 => 87,
+            TextureFormat::R8 => 61,
+        }
+    }
+
+    fn from_dxgi_format(dxgi_format: u32) -> Option<TextureFormat> {
+        match dxgi_format {
+            70..=72 => Some(TextureFormat::Bc1),
+            73..=75 => Some(TextureFormat::Bc2),
+            76..=78 => Some(TextureFormat::Bc3),
+            79..=81 => Some(TextureFormat::Bc4),
+            82..=84 => Some(TextureFormat::Bc5),
+            94..=96 => Some(TextureFormat::Bc6h),
+            97..=99 => Some(TextureFormat::Bc7),
+            27..=29 => Some(TextureFormat::Rgba8),
+            87 | 91 => Some(TextureFormat::Bgra8),
+            60 | 61 | 65 => Some(TextureFormat::R8),
+            _ => None,
+        }
+    }
+
+    //the legacy four character code. formats without one need a DX10 header.
+    fn four_cc(self) -> Option<&'static [u8; 4]> {
+        match self {
+            TextureFormat::Bc1 => Some(b"DXT1"),
+            TextureFormat::Bc2 => Some(b"DXT3"),
+            TextureFormat::Bc3 => Some(b"DXT5"),
+            TextureFormat::Bc4 => Some(b"ATI1"),
+            TextureFormat::Bc5 => Some(b"ATI2"),
+            _ => None,
+        }
+    }
+
+    //(flags, bit count, r, g, b, a) of the uncompressed formats.
+    fn masks(self) -> Option<(u32, u32, u32, u32, u32, u32)> {
+        match self {
+            TextureFormat::Rgba8 => Some((
+                DDPF_RGB | DDPF_ALPHAPIXELS,
+                32,
+                0xff,
+                0xff00,
+                0xff0000,
+                0xff000000,
+            )),
+            TextureFormat::Bgra8 => Some((
+                DDPF_RGB | DDPF_ALPHAPIXELS,
+                32,
+                0xff0000,
+                0xff00,
+                0xff,
+                0xff000000,
+            )),
+            TextureFormat::R8 => Some((DDPF_LUMINANCE, 8, 0xff, 0, 0, 0)),
+            _ => None,
+        }
+    }
+
+    //bytes per 4x4 block for block compressed formats.
+    pub fn block_size(self) -> Option<usize> {
+        match self {
+            TextureFormat::Bc1 | TextureFormat::Bc4 => Some(8),
+            TextureFormat::Bc2
+            | TextureFormat::Bc3
+            | TextureFormat::Bc5
+            | TextureFormat::Bc6h
+            | TextureFormat::Bc7 => Some(16),
+            _ => None,
+        }
+    }
+
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            TextureFormat::R8 => 1,
+            _ => 4,
+        }
+    }
+
+    //the bytes of one mip level of one face. volume textures have every slice in it.
+    //none if the size does not fit in memory.
+    pub fn surface_size(self, width: u32, height: u32, depth: u32) -> Option<usize> {
+        let (width, height, depth) = (width as usize, height as usize, depth as usize);
+        let (columns, rows, unit) = match self.block_size() {
+            Some(block_size) => (
+                width.div_ceil(4).max(1),
+                height.div_ceil(4).max(1),
+                block_size,
+            ),
+            None => (width, height, self.bytes_per_pixel()),
+        };
+        columns
+            .checked_mul(rows)?
+            .checked_mul(unit)?
+            .checked_mul(depth)
+    }
+}
+
+//a texture resource is a u32 of flags, the u32 size of an embedded dds, the dds and then its
+//stream references: a u32 count and a (u32 offset, u32 size) into the variant's streamed data
+//for each surface kept in the .stream file. the embedded dds describes the whole texture but only
+//has the smaller mips. the largest mips of every face are the ones streamed, in dds order.
+#[derive(Clone, Debug)]
+pub struct Texture {
+    pub flags: u32,
+    pub format: TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32, //1 unless it is a volume texture.
+    pub mip_count: u32,
+    pub cubemap: bool,
+    pub data: Vec<u8>, //every surface: each face's mips from largest to smallest, like a dds.
+}
+
+impl Texture {
+    pub fn parse(resource: &[u8], stream: &[u8]) -> Result<Texture, UnbundlerError> {
+        let mut bytes = ByteStream::new(resource.to_vec());
+        let flags = bytes.read_uint()?;
+        let dds_size = bytes.read_uint()?;
+        let dds = bytes.read(dds_size as usize)?;
+
+        let mut stream_references = vec![];
+        if bytes.remaining_bytes() >= 4 {
+            for _ in 0..bytes.read_uint()? {
+                stream_references.push((bytes.read_uint()? as usize, bytes.read_uint()? as usize));
+            }
+        }
+
+        let (mut texture, embedded) = Texture::parse_dds(&dds)?;
+        texture.flags = flags;
+
+        let faces = texture.faces();
+        if stream_references.len() % faces != 0
+            || stream_references.len() / faces > texture.mip_count as usize
+        {
+            return Err(UnbundlerError::Texture(format!(
+                "{} streamed surfaces do not fit {} faces of {} mips",
+                stream_references.len(),
+                faces,
+                texture.mip_count
+            )));
+        }
+
+        let streamed_mips = stream_references.len() / faces;
+        let mut stream_references = stream_references.into_iter();
+        let mut embedded_offset = 0;
+        let mut data = vec![];
+        for _face in 0..faces {
+            for level in 0..texture.mip_count {
+                let size = texture.surface_size(level)?;
+                let surface = if (level as usize) < streamed_mips {
+                    let (offset, stream_size) = stream_references.next().unwrap();
+                    if stream_size != size {
+                        return Err(UnbundlerError::Texture(format!(
+                            "mip {} is {} bytes in the stream but should be {}",
+                            level, stream_size, size
+                        )));
+                    }
+                    offset
+                        .checked_add(size)
+                        .and_then(|end| stream.get(offset..end))
+                } else {
+                    embedded_offset += size;
+                    embedded.get(embedded_offset - size..embedded_offset)
+                };
+
+                match surface {
+                    Some(surface) => data.extend(surface),
+                    None => {
+                        return Err(UnbundlerError::Texture(format!(
+                            "mip {} is missing from the texture",
+                            level
+                        )))
+                    }
+                }
+            }
+        }
+
+        texture.data = data;
+        Ok(texture)
+    }
+
+    //a standalone dds as written by any tool, with every surface in it.
+    pub fn from_dds(dds: &[u8]) -> Result<Texture, UnbundlerError> {
+        let (mut texture, data) = Texture::parse_dds(dds)?;
+        let size = texture.face_size()? * texture.faces();
+
+        match data.get(..size) {
+            Some(data) => texture.data = data.to_vec(),
+            None => {
+                return Err(UnbundlerError::Texture(format!(
+                    "the dds has {} bytes of surfaces but needs {}",
+                    data.len(),
+                    size
+                )))
+            }
+        }
+        Ok(texture)
+    }
+
+    //reads the header of a dds and returns the texture without data, and the data after the header.
+    fn parse_dds(dds: &[u8]) -> Result<(Texture, &[u8]), UnbundlerError> {
+        if dds.len() < 128 || &dds[0..4] != DDS_MAGIC {
+            return Err(UnbundlerError::Texture(
+                "the texture does not contain a dds".to_string(),
+            ));
+        }
+
+        let field =
+            |index: usize| u32::from_le_bytes(dds[index * 4..index * 4 + 4].try_into().unwrap());
+        let header_flags = field(2);
+        let height = field(3);
+        let width = field(4);
+        let depth = field(6);
+        let mip_count = field(7);
+        let pixel_flags = field(20);
+        let four_cc = &dds[84..88];
+        let bit_count = field(22);
+        let red_mask = field(23);
+        let caps2 = field(28);
+
+        let mut data_offset = 128;
+        let mut cubemap = caps2 & DDSCAPS2_CUBEMAP_ALL_FACES != 0;
+        let format = if pixel_flags & DDPF_FOURCC != 0 {
+            match four_cc {
+                b"DXT1" => Some(TextureFormat::Bc1),
+                b"DXT2" | b"DXT3" => Some(TextureFormat::Bc2),
+                b"DXT4" | b"DXT5" => Some(TextureFormat::Bc3),
+                b"ATI1" | b"BC4U" => Some(TextureFormat::Bc4),
+                b"ATI2" | b"BC5U" => Some(TextureFormat::Bc5),
+                b"DX10" if dds.len() >= 148 => {
+                    data_offset = 148;
+                    let dx10_field = |index: usize| {
+                        u32::from_le_bytes(
+                            dds[128 + index * 4..132 + index * 4].try_into().unwrap(),
+                        )
+                    };
+                    cubemap |= dx10_field(2) & MISC_TEXTURECUBE != 0;
+                    TextureFormat::from_dxgi_format(dx10_field(0))
+                }
+                _ => None,
+            }
+        } else {
+            match (bit_count, red_mask) {
+                (32, 0xff) => Some(TextureFormat::Rgba8),
+                (32, 0xff0000) => Some(TextureFormat::Bgra8),
+                (8, _) if pixel_flags & (DDPF_LUMINANCE | DDPF_ALPHA) != 0 => {
+                    Some(TextureFormat::R8)
+                }
+                _ => None,
+            }
+        };
+
+        let format = format.ok_or_else(|| {
+            UnbundlerError::Texture(format!(
+                "unsupported dds pixel format {:?} {:#x} {} bits",
+                String::from_utf8_lossy(four_cc),
+                pixel_flags,
+                bit_count
+            ))
+        })?;
+
+        let depth = if header_flags & DDSD_DEPTH != 0 && caps2 & DDSCAPS2_VOLUME != 0 {
+            depth
+        } else {
+            1
+        };
+        if width == 0 || height == 0 || depth == 0 {
+            return Err(UnbundlerError::Texture(format!(
+                "the dds is {}x{}x{}, which has no pixels",
+                width, height, depth
+            )));
+        }
+        if width > MAX_DIMENSION || height > MAX_DIMENSION || depth > MAX_DEPTH {
+            return Err(UnbundlerError::Texture(format!(
+                "the dds is {}x{}x{}, which is larger than any texture can be",
+                width, height, depth
+            )));
+        }
+        if mip_count > MAX_MIP_COUNT {
+            return Err(UnbundlerError::Texture(format!(
+                "the dds has {} mips, but there can be no more than {}",
+                mip_count, MAX_MIP_COUNT
+            )));
+        }
+
+        let texture = Texture {
+            flags: 0,
+            format,
+            width,
+            height,
+            depth,
+            mip_count: mip_count.max(1),
+            cubemap,
+            data: vec![],
+        };
+        Ok((texture, &dds[data_offset..]))
+    }
+
+    pub fn faces(&self) -> usize {
+        if self.cubemap {
+            6
+        } else {
+            1
+        }
+    }
+
+    pub fn mip_dimensions(&self, level: u32) -> (u32, u32, u32) {
+        let mip = |size: u32| size.checked_shr(level).unwrap_or(0).max(1);
+        (mip(self.width), mip(self.height), mip(self.depth))
+    }
+
+    pub fn surface_size(&self, level: u32) -> Result<usize, UnbundlerError> {
+        let (width, height, depth) = self.mip_dimensions(level);
+        self.format
+            .surface_size(width, height, depth)
+            .ok_or_else(|| {
+                UnbundlerError::Texture(format!(
+                    "mip {} of a {}x{}x{} texture is too large",
+                    level, width, height, depth
+                ))
+            })
+    }
+
+    //the bytes of every mip of one face.
+    fn face_size(&self) -> Result<usize, UnbundlerError> {
+        (0..self.mip_count)
+            .map(|level| self.surface_size(level))
+            .sum()
+    }
+
+    //the data of one mip level of one face.
+    pub fn surface(&self, face: usize, level: u32) -> Result<&[u8], UnbundlerError> {
+        let offset = face * self.face_size()?
+            + (0..level.min(self.mip_count))
+                .map(|level| self.surface_size(level))
+                .sum::<Result<usize, UnbundlerError>>()?;
+
+        self.data
+            .get(offset..offset + self.surface_size(level)?)
+            .filter(|_| face < self.faces() && level < self.mip_count)
+            .ok_or_else(|| {
+                UnbundlerError::Texture(format!(
+                    "the texture has no mip {} of face {}",
+                    level, face
+                ))
+            })
+    }
+
+    //rgba8 pixels of one mip level of one face. the slices of a volume texture are stacked from top to bottom.
+    pub fn to_rgba(&self, face: usize, level: u32) -> Result<(u32, u32, Vec<u8>), UnbundlerError> {
+        let surface = self.surface(face, level)?;
+        let (width, height, depth) = self.mip_dimensions(level);
+        let slice_size = surface.len() / depth as usize;
+
+        let mut pixels = vec![];
+        for slice in surface.chunks_exact(slice_size) {
+            pixels.extend(bcn::decode(self.format, slice, width, height)?);
+        }
+        Ok((width, height * depth, pixels))
+    }
+
+    pub fn to_png(&self, face: usize, level: u32) -> Result<Vec<u8>, UnbundlerError> {
+        let (width, height, pixels) = self.to_rgba(face, level)?;
+        let png_error = |e: png::EncodingError| UnbundlerError::Texture(e.to_string());
+
+        let mut png = vec![];
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&pixels).map_err(png_error)?;
+        writer.finish().map_err(png_error)?;
+        Ok(png)
+    }
+
+    //the width, height and rgba8 pixels of a png of any color type.
+    pub fn read_png(png: &[u8]) -> Result<(u32, u32, Vec<u8>), UnbundlerError> {
+        let png_error = |e: png::DecodingError| UnbundlerError::Texture(e.to_string());
+
+        let mut decoder = png::Decoder::new(png);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(png_error)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(png_error)?;
+        let buffer = &buffer[..info.buffer_size()];
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer.to_vec(),
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer
+                .iter()
+                .flat_map(|value| [*value, *value, *value, 255])
+                .collect(),
+            png::ColorType::Indexed => {
+                return Err(UnbundlerError::Texture(
+                    "the png palette could not be expanded".to_string(),
+                ))
+            }
+        };
+        Ok((info.width, info.height, pixels))
+    }
+
+    //a 2d texture encoded from rgba8 pixels, with every mip down to 1x1 when mips are generated.
+    pub fn from_rgba(
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+        generate_mips: bool,
+    ) -> Result<Texture, UnbundlerError> {
+        let mip_count = if generate_mips {
+            32 - width.max(height).max(1).leading_zeros()
+        } else {
+            1
+        };
+
+        let mut data = vec![];
+        let (mut mip_width, mut mip_height, mut mip) = (width, height, pixels);
+        for level in 0..mip_count {
+            if level > 0 {
+                mip = Texture::downsample(&mip, mip_width, mip_height);
+                mip_width = (mip_width / 2).max(1);
+                mip_height = (mip_height / 2).max(1);
+            }
+            data.extend(bcn::encode(format, &mip, mip_width, mip_height)?);
+        }
+
+        Ok(Texture {
+            flags: 0,
+            format,
+            width,
+            height,
+            depth: 1,
+            mip_count,
+            cubemap: false,
+            data,
+        })
+    }
+
+    //joins six textures of the same size and format into the faces of a cubemap.
+    pub fn cubemap(faces: Vec<Texture>) -> Result<Texture, UnbundlerError> {
+        let shape = |face: &Texture| {
+            (
+                face.format,
+                face.width,
+                face.height,
+                face.depth,
+                face.mip_count,
+                face.cubemap,
+            )
+        };
+        if faces.len() != 6 || faces.iter().any(|face| shape(face) != shape(&faces[0])) {
+            return Err(UnbundlerError::Texture(
+                "a cubemap needs six faces of the same size".to_string(),
+            ));
+        }
+
+        let data = faces
+            .iter()
+            .flat_map(|face| face.data.iter().copied())
+            .collect();
+        let first = faces.into_iter().next().unwrap();
+        Ok(Texture {
+            cubemap: true,
+            data,
+            ..first
+        })
+    }
+
+    //halves an rgba8 image by averaging each 2x2 square. odd edges reuse their last row or column.
+    fn downsample(pixels: &[u8], width: u32, height: u32) -> Vec<u8> {
+        let (width, height) = (width as usize, height as usize);
+        let (half_width, half_height) = ((width / 2).max(1), (height / 2).max(1));
+
+        let mut half = Vec::with_capacity(half_width * half_height * 4);
+        for y in 0..half_height {
+            for x in 0..half_width {
+                let rows = [(y * 2).min(height - 1), (y * 2 + 1).min(height - 1)];
+                let columns = [(x * 2).min(width - 1), (x * 2 + 1).min(width - 1)];
+                for channel in 0..4 {
+                    let sum: u32 = rows
+                        .iter()
+                        .flat_map(|row| columns.iter().map(move |column| (row, column)))
+                        .map(|(row, column)| pixels[(row * width + column) * 4 + channel] as u32)
+                        .sum();
+                    half.push(((sum + 2) / 4) as u8);
+                }
+            }
+        }
+        half
+    }
+
+    //the texture re-encoded in another format from the largest mip of each face.
+    pub fn convert(
+        &self,
+        format: TextureFormat,
+        generate_mips: bool,
+    ) -> Result<Texture, UnbundlerError> {
+        if self.depth > 1 {
+            return Err(UnbundlerError::Texture(
+                "volume textures can only be replaced by a dds in the same format".to_string(),
+            ));
+        }
+
+        let mut texture = Texture {
+            format,
+            mip_count: 0,
+            data: vec![],
+            ..self.clone()
+        };
+        for face in 0..self.faces() {
+            let (width, height, pixels) = self.to_rgba(face, 0)?;
+            let face = Texture::from_rgba(format, width, height, pixels, generate_mips)?;
+            texture.mip_count = face.mip_count;
+            texture.data.extend(face.data);
+        }
+        Ok(texture)
+    }
+
+    //this texture as a replacement for the original: in its format, with its flags,
+    //and with mips if the original has them.
+    pub fn matching(self, original: &Texture) -> Result<Texture, UnbundlerError> {
+        if self.cubemap != original.cubemap || (self.depth > 1) != (original.depth > 1) {
+            return Err(UnbundlerError::Texture(
+                "a replacement must be a cubemap or volume texture when the original is one"
+                    .to_string(),
+            ));
+        }
+
+        let generate_mips = original.mip_count > 1;
+        let mut texture = if self.format == original.format && (self.mip_count > 1) == generate_mips
+        {
+            self
+        } else {
+            self.convert(original.format, generate_mips)?
+        };
+        texture.flags = original.flags;
+        Ok(texture)
+    }
+
+    //a dds file with the legacy header where the format has one and a DX10 header otherwise.
+    pub fn to_dds(&self) -> Vec<u8> {
+        let compressed = self.format.block_size().is_some();
+        let volume = self.depth > 1;
+
+        let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+        let pitch_or_linear_size = if compressed {
+            flags |= DDSD_LINEARSIZE;
+            self.format
+                .surface_size(self.width, self.height, 1)
+                .unwrap_or(0) as u32
+        } else {
+            flags |= DDSD_PITCH;
+            self.width * self.format.bytes_per_pixel() as u32
+        };
+        if self.mip_count > 1 {
+            flags |= DDSD_MIPMAPCOUNT;
+        }
+        if volume {
+            flags |= DDSD_DEPTH;
+        }
+
+        let mut caps = DDSCAPS_TEXTURE;
+        if self.mip_count > 1 {
+            caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+        }
+        if self.cubemap || volume {
+            caps |= DDSCAPS_COMPLEX;
+        }
+        let mut caps2 = 0;
+        if self.cubemap {
+            caps2 |= DDSCAPS2_CUBEMAP_ALL_FACES;
+        }
+        if volume {
+            caps2 |= DDSCAPS2_VOLUME;
+        }
+
+        let mut dds = vec![];
+        dds.extend(DDS_MAGIC);
+        for value in [
+            HEADER_SIZE,
+            flags,
+            self.height,
+            self.width,
+            pitch_or_linear_size,
+            if volume { self.depth } else { 0 },
+            self.mip_count,
+        ] {
+            dds.extend(value.to_le_bytes());
+        }
+        dds.extend([0u8; 44]); //dwReserved1
+
+        let pixel_format = match (self.format.four_cc(), self.format.masks()) {
+            (Some(four_cc), _) => [DDPF_FOURCC, u32::from_le_bytes(*four_cc), 0, 0, 0, 0, 0],
+            (None, Some((pixel_flags, bits, r, g, b, a))) => [pixel_flags, 0, bits, r, g, b, a],
+            (None, None) => [DDPF_FOURCC, u32::from_le_bytes(*b"DX10"), 0, 0, 0, 0, 0],
+        };
+        dds.extend(PIXEL_FORMAT_SIZE.to_le_bytes());
+        for value in pixel_format {
+            dds.extend(value.to_le_bytes());
+        }
+
+        for value in [caps, caps2, 0, 0, 0] {
+            dds.extend(value.to_le_bytes());
+        }
+
+        if pixel_format[1] == u32::from_le_bytes(*b"DX10") {
+            let dimension = if volume {
+                DIMENSION_TEXTURE3D
+            } else {
+                DIMENSION_TEXTURE2D
+            };
+            let misc = if self.cubemap { MISC_TEXTURECUBE } else { 0 };
+            for value in [self.format.dxgi_format(), dimension, misc, 1, 0] {
+                dds.extend(value.to_le_bytes());
+            }
+        }
+
+        dds.extend(&self.data);
+        dds
+    }
+
+    //a texture resource with every mip embedded and nothing streamed.
+    pub fn to_resource(&self) -> Vec<u8> {
+        let dds = self.to_dds();
+        let mut resource = vec![];
+        resource.extend(self.flags.to_le_bytes());
+        resource.extend((dds.len() as u32).to_le_bytes());
+        resource.extend(dds);
+        resource.extend(0u32.to_le_bytes());
+        resource
+    }
+}
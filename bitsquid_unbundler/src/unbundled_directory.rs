@@ -1,13 +1,23 @@
+use crate::bundle_header::BundleHeader;
 use crate::unbundled_file::UnbundledFile;
 
 pub struct UnbundledDirectory {
     pub dir_name: String,
+    pub header: BundleHeader,
     pub files: Vec<UnbundledFile>,
 }
 
 impl UnbundledDirectory {
-    pub fn new(dir_name: String, files: Vec<UnbundledFile>) -> UnbundledDirectory {
-        UnbundledDirectory { dir_name, files }
+    pub fn new(
+        dir_name: String,
+        header: BundleHeader,
+        files: Vec<UnbundledFile>,
+    ) -> UnbundledDirectory {
+        UnbundledDirectory {
+            dir_name,
+            header,
+            files,
+        }
     }
 
     pub fn push(&mut self, file: UnbundledFile) {
@@ -1,6 +1,41 @@
+use crate::bundle_header::BundleEntryHeader;
+
 #[derive(Clone)]
 pub struct UnbundledFile {
     pub path: u64, //u64 path because it is a hash.
     pub extension: String,
+    pub variants: Vec<ResourceVariant>,
+    pub header: BundleEntryHeader,
+}
+
+//a resource may be stored once per language.
+#[derive(Clone)]
+pub struct ResourceVariant {
+    pub language: u32,
+    pub flags: u32, //with a .stream file beside the bundle, how many of its bytes are the variant's. kept as it is without one.
     pub data: Vec<u8>,
+    pub stream: Vec<u8>, //streamed data such as texture mips and audio, read from the .stream file.
+}
+
+impl UnbundledFile {
+    //what the file is named when its real name is not known.
+    pub fn hex_name(&self) -> String {
+        format!("{:#x}", self.path)
+    }
+
+    //the default language keeps the plain {name}.{extension} name.
+    //the name is the hex name or a restored name such as scripts/game/player.
+    //the extension is passed in since it may be presented differently, e.g. textures as dds.
+    pub fn variant_file_name(
+        &self,
+        name: &str,
+        variant: &ResourceVariant,
+        extension: &str,
+    ) -> String {
+        if variant.language == 0 {
+            format!("{}.{}", name, extension)
+        } else {
+            format!("{}.lang_{:02}.{}", name, variant.language, extension)
+        }
+    }
 }
@@ -1,192 +1,274 @@
-use std::io::Write;
-use std::path::PathBuf;
-use std::{fs, io};
-
-use flate2::write::ZlibDecoder;
-
-use crate::byte_stream::ByteStream;
-use crate::extensions::Extensions;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::{error, fmt};
+
+use crate::bundle_index::BundleIndex;
+use crate::bundle_reader::BundleReader;
+use crate::file_filter::FileFilter;
+use crate::game_profile::GameProfile;
 use crate::unbundled_directory::UnbundledDirectory;
 use crate::unbundled_file::UnbundledFile;
 
+#[derive(Clone)]
 pub struct Unbundler {
     pub file_path: PathBuf,
-    pub dds_mode: bool,
+    pub filter: FileFilter,
+    pub profile: Option<GameProfile>, //detected from each bundle's header when not given.
 }
 
 impl Unbundler {
     pub fn unbundle(&self) -> Result<Vec<UnbundledDirectory>, UnbundlerError> {
         let mut unbundled_dirs = vec![];
 
-        if self.file_path.is_file() {
-            if !Unbundler::has_valid_extension(&self.file_path)? {
-                return Err(UnbundlerError::Extension(
-                    format!("Attempted to unbundle a file which has an invalid extension. Do not unbundle files with extensions: .ini, .stream, or .data"
-                )));
+        for bundle_path in self.bundle_paths()? {
+            if let Some(unbundled) = self.skip_bad_bundle(self.parse_bundle(&bundle_path))? {
+                unbundled_dirs.push(unbundled);
             }
+        }
 
-            let file_path = String::from(self.file_path.to_str().ok_or(UnbundlerError::NotUTF8)?);
-            let file_name = String::from(
-                self.file_path
-                    .file_name()
-                    .ok_or(UnbundlerError::NoFileName)?
-                    .to_str()
-                    .ok_or(UnbundlerError::NotUTF8)?,
-            );
-            let unbundled = self.parse_bundle(&file_name, &file_path)?;
-            unbundled_dirs.push(unbundled);
-            return Ok(unbundled_dirs);
-        }
-
-        let read_dir = self.file_path.read_dir()?;
-
-        for entry in read_dir {
-            if !Unbundler::has_valid_extension(&entry.as_ref()?.path())? {
-                print!(
-                    "Skipping file with invalid extension: {:?}",
-                    &entry.as_ref()?.path().to_str(),
-                );
-                continue;
+        Ok(unbundled_dirs)
+    }
+
+    //visits every file of every bundle as soon as it is inflated instead of holding them all in memory.
+    pub fn for_each_file<F>(&self, mut visit: F) -> Result<(), UnbundlerError>
+    where
+        F: FnMut(&str, UnbundledFile),
+    {
+        for bundle_path in self.bundle_paths()? {
+            self.skip_bad_bundle(self.visit_bundle(&bundle_path, &mut visit))?;
+        }
+
+        Ok(())
+    }
+
+    //same as for_each_file, but bundles are spread across a pool of worker threads.
+    //each bundle is still visited by a single thread, so the files written do not depend on the job count.
+    pub fn for_each_file_parallel<F>(&self, jobs: usize, visit: F) -> Result<(), UnbundlerError>
+    where
+        F: Fn(&str, UnbundledFile) + Sync,
+    {
+        let bundle_paths = self.bundle_paths()?;
+        let next_bundle = AtomicUsize::new(0);
+        let errors = Mutex::new(vec![]);
+
+        thread::scope(|scope| {
+            for _i in 0..jobs.max(1) {
+                scope.spawn(|| loop {
+                    let index = next_bundle.fetch_add(1, Ordering::SeqCst);
+                    let Some(bundle_path) = bundle_paths.get(index) else {
+                        break;
+                    };
+
+                    let result = self.visit_bundle(bundle_path, &visit);
+                    if let Err(e) = self.skip_bad_bundle(result) {
+                        errors.lock().unwrap().push((index, e));
+                    }
+                });
             }
+        });
+
+        //report the error of the first bundle in directory order, like the serial version would.
+        match errors
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .min_by_key(|(index, _)| *index)
+        {
+            Some((_, e)) => Err(e),
+            None => Ok(()),
+        }
+    }
 
-            if let Some(file_path) = entry.as_ref()?.path().to_str() {
-                if let Some(file_name) = entry.as_ref()?.file_name().to_str() {
-                    let unbundled =
-                        self.parse_bundle(&String::from(file_name), &String::from(file_path))?;
-                    unbundled_dirs.push(unbundled);
-                }
+    //lists the files of every bundle without keeping their data.
+    pub fn index(&self) -> Result<Vec<BundleIndex>, UnbundlerError> {
+        let mut indices = vec![];
+
+        for bundle_path in self.bundle_paths()? {
+            let index = self.index_bundle(&bundle_path);
+            if let Some(index) = self.skip_bad_bundle(index)? {
+                indices.push(index);
             }
         }
 
-        Ok(unbundled_dirs)
+        Ok(indices)
     }
 
-    fn has_valid_extension(path: &PathBuf) -> Result<bool, UnbundlerError> {
-        match path.extension() {
-            Some(ext) => match ext.to_str().ok_or(UnbundlerError::NotUTF8)? {
-                "stream" | "ini" | "data" => Ok(false),
-                _ => Ok(true),
-            },
-            None => Ok(true),
+    //the number of files across all bundles which pass the filter, read from the name tables only.
+    pub fn count_files(&self) -> Result<usize, UnbundlerError> {
+        let mut total = 0;
+
+        for bundle_path in self.bundle_paths()? {
+            if let Some(bundle_reader) = self.skip_bad_bundle(self.read_bundle(&bundle_path))? {
+                total += bundle_reader
+                    .header
+                    .names
+                    .iter()
+                    .filter(|(extension, path)| self.filter.matches(*extension, *path))
+                    .count();
+            }
         }
+
+        Ok(total)
     }
 
-    fn parse_bundle(
+    pub fn read_bundle(
         &self,
-        file_name: &String,
-        file_path: &String,
-    ) -> Result<UnbundledDirectory, UnbundlerError> {
-        let file = fs::read(file_path)?;
-        let mut compressed_stream = ByteStream::new(file);
-        match self.read_unbundled_files(&mut compressed_stream) {
-            Ok(files) => Ok(UnbundledDirectory::new(String::from(file_name), files)),
-            Err(e) => Err(UnbundlerError::Inflater(format!(
-                "Error inflating: {}\n{:?}",
-                file_path, e
-            ))),
+        bundle_path: &Path,
+    ) -> Result<BundleReader<BufReader<File>>, UnbundlerError> {
+        let file = BufReader::new(File::open(bundle_path)?);
+        let bundle_reader = BundleReader::new(file, self.profile.clone(), self.filter.clone())
+            .map_err(|e| Unbundler::inflater_error(bundle_path, e))?;
+
+        if !bundle_reader.profile.streams {
+            return Ok(bundle_reader);
+        }
+        match File::open(Unbundler::stream_path(bundle_path)) {
+            Ok(stream_file) => Ok(bundle_reader.with_stream(stream_file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(bundle_reader),
+            Err(e) => Err(e.into()),
         }
     }
 
-    fn read_unbundled_files(
-        &self,
-        compressed_stream: &mut ByteStream,
-    ) -> Result<Vec<UnbundledFile>, UnbundlerError> {
-        let mut unbundled_files: Vec<UnbundledFile> = vec![];
-
-        let mut inflated_stream = match self.inflate_stream(compressed_stream) {
-            Ok(inflated_stream) => inflated_stream,
-            Err(e) => return Err(e),
-        };
+    //streamed data is kept beside the bundle in a file of the same name with a .stream extension.
+    pub fn stream_path(bundle_path: &Path) -> PathBuf {
+        let mut stream_path = bundle_path.as_os_str().to_owned();
+        stream_path.push(".stream");
+        PathBuf::from(stream_path)
+    }
 
-        let file_count = inflated_stream.read_uint();
-        let _checksum = inflated_stream.read(256);
-        let _file_names_and_extensions = inflated_stream.read((16 * file_count) as usize);
+    pub fn bundle_paths(&self) -> Result<Vec<PathBuf>, UnbundlerError> {
+        if self.file_path.is_file() {
+            if !self.has_valid_extension(&self.file_path)? {
+                return Err(UnbundlerError::Extension(format!(
+                    "Attempted to unbundle a file which has an invalid extension. Do not unbundle files with extensions: {}",
+                    self.skipped_extensions().join(", ")
+                )));
+            }
 
-        for _i in 0..file_count {
-            let unbundled_file = self.read_unbundled_file(&mut inflated_stream);
-            unbundled_files.push(unbundled_file);
+            return Ok(vec![self.file_path.clone()]);
         }
 
-        Ok(unbundled_files)
-    }
+        let mut bundle_paths = vec![];
 
-    fn inflate_stream(
-        &self,
-        compressed_stream: &mut ByteStream,
-    ) -> Result<ByteStream, UnbundlerError> {
-        let _header = compressed_stream.read_uint();
-        let _size = compressed_stream.read_uint();
-        let _reserved = compressed_stream.read_uint();
-
-        let mut inflated: Vec<u8> = vec![];
-
-        while compressed_stream.remaining_bytes() > 0 {
-            match self.append_block(compressed_stream, &mut inflated) {
-                Ok(_) => {}
-                Err(e) => return Err(e),
+        for entry in self.file_path.read_dir()? {
+            let path = entry?.path();
+
+            if !path.is_file() {
+                continue;
+            }
+
+            if !self.has_valid_extension(&path)? {
+                eprintln!("Skipping file with invalid extension: {:?}", path.to_str());
+                continue;
             }
+
+            bundle_paths.push(path);
         }
 
-        Ok(ByteStream::new(inflated))
+        bundle_paths.sort();
+        Ok(bundle_paths)
     }
 
-    fn read_unbundled_file(&self, inflated_stream: &mut ByteStream) -> UnbundledFile {
-        let extension = inflated_stream.read_ulong();
-        let path = inflated_stream.read_ulong();
-        let has_data = inflated_stream.read_ulong();
+    fn has_valid_extension(&self, path: &Path) -> Result<bool, UnbundlerError> {
+        let Some(ext) = path.extension() else {
+            return Ok(true);
+        };
+        let ext = ext.to_str().ok_or(UnbundlerError::NotUTF8)?;
 
-        let data;
-        if has_data > 0 {
-            let _flag = inflated_stream.read_uint();
-            let size = inflated_stream.read_uint();
-            let _unknown2 = inflated_stream.read_uint();
-            data = inflated_stream.read(size as usize);
-        } else {
-            data = vec![];
-        }
+        Ok(!self
+            .skipped_extensions()
+            .iter()
+            .any(|skipped| skipped.eq_ignore_ascii_case(ext)))
+    }
 
-        UnbundledFile {
-            extension: Extensions::lookup(extension, self.dds_mode),
-            path,
-            data,
+    //bundles are not read yet when listing them, so without a profile the defaults are skipped.
+    fn skipped_extensions(&self) -> Vec<String> {
+        match self.profile.as_ref() {
+            Some(profile) => profile.skipped_extensions.clone(),
+            None => GameProfile::default().skipped_extensions,
         }
     }
 
-    fn append_block(
-        &self,
-        compressed_stream: &mut ByteStream,
-        buffer: &mut Vec<u8>,
-    ) -> Result<(), UnbundlerError> {
-        let len = compressed_stream.read_uint();
+    fn file_name(bundle_path: &Path) -> Result<String, UnbundlerError> {
+        Ok(String::from(
+            bundle_path
+                .file_name()
+                .ok_or(UnbundlerError::NoFileName)?
+                .to_str()
+                .ok_or(UnbundlerError::NotUTF8)?,
+        ))
+    }
 
-        if len == (1 << 16) {
-            buffer.append(&mut compressed_stream.read(len as usize))
-        } else {
-            let mut block = match self.decompress_block(compressed_stream, len as usize) {
-                Ok(block) => block,
-                Err(e) => return Err(e),
-            };
-            buffer.append(&mut block);
+    fn visit_bundle<F>(&self, bundle_path: &Path, mut visit: F) -> Result<(), UnbundlerError>
+    where
+        F: FnMut(&str, UnbundledFile),
+    {
+        let file_name = Unbundler::file_name(bundle_path)?;
+
+        for unbundled_file in self.read_bundle(bundle_path)? {
+            let unbundled_file =
+                unbundled_file.map_err(|e| Unbundler::inflater_error(bundle_path, e))?;
+            visit(&file_name, unbundled_file);
         }
 
         Ok(())
     }
 
-    fn decompress_block(
-        &self,
-        compressed_stream: &mut ByteStream,
-        len: usize,
-    ) -> Result<Vec<u8>, UnbundlerError> {
-        let mut decoder = ZlibDecoder::new(vec![]);
+    fn index_bundle(&self, bundle_path: &Path) -> Result<BundleIndex, UnbundlerError> {
+        let entries = self
+            .read_bundle(bundle_path)?
+            .read_index_entries()
+            .map_err(|e| Unbundler::inflater_error(bundle_path, e))?;
 
-        match decoder.write_all(&compressed_stream.read(len as usize)) {
-            Ok(_) => {}
-            Err(_) => return Err(UnbundlerError::DecoderWriteAll),
+        Ok(BundleIndex {
+            bundle_name: Unbundler::file_name(bundle_path)?,
+            entries,
+        })
+    }
+
+    fn parse_bundle(&self, bundle_path: &Path) -> Result<UnbundledDirectory, UnbundlerError> {
+        let file_name = Unbundler::file_name(bundle_path)?;
+        let mut bundle_reader = self.read_bundle(bundle_path)?;
+
+        let mut files = vec![];
+        for unbundled_file in bundle_reader.by_ref() {
+            files.push(unbundled_file.map_err(|e| Unbundler::inflater_error(bundle_path, e))?);
         }
 
-        match decoder.finish() {
-            Ok(block) => Ok(block),
-            Err(_) => Err(UnbundlerError::DecoderFinish),
+        Ok(UnbundledDirectory::new(
+            file_name,
+            bundle_reader.header,
+            files,
+        ))
+    }
+
+    //a bad bundle in a directory is reported and skipped so that the rest can still be unbundled.
+    //when unbundling a single file there is nothing left to do, so the error is returned instead.
+    fn skip_bad_bundle<T>(
+        &self,
+        result: Result<T, UnbundlerError>,
+    ) -> Result<Option<T>, UnbundlerError> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if self.file_path.is_dir() => {
+                eprintln!("Skipping bundle which could not be unbundled: {}", e);
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    //a .stream file which does not match its bundle is not an inflating error, but it still names the bundle.
+    fn inflater_error(bundle_path: &Path, e: UnbundlerError) -> UnbundlerError {
+        match e {
+            UnbundlerError::Stream(message) => {
+                UnbundlerError::Stream(format!("{:?}: {}", bundle_path, message))
+            }
+            e => UnbundlerError::Inflater(format!("Error inflating: {:?}\n{}", bundle_path, e)),
         }
     }
 }
@@ -200,8 +282,43 @@ pub enum UnbundlerError {
     Extension(String),
     NotUTF8,
     NoFileName,
+    InvalidFileName(String),
+    InvalidHash(String),
+    Json(String),
+    Profile(String),
+    EncoderFinish,
+    EncoderWriteAll,
+    UnexpectedEof { offset: usize, wanted: usize },
+    UlebOverflow { offset: usize },
+    Texture(String),
+    Strings(String),
+    Stream(String),
+    TooLarge(String),
 }
 
+impl fmt::Display for UnbundlerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnbundlerError::UnexpectedEof { offset, wanted } => write!(
+                f,
+                "Unexpected end of stream at offset {:#x} while reading {} bytes.",
+                offset, wanted
+            ),
+            UnbundlerError::Inflater(message)
+            | UnbundlerError::Profile(message)
+            | UnbundlerError::Texture(message)
+            | UnbundlerError::Strings(message)
+            | UnbundlerError::Stream(message)
+            | UnbundlerError::TooLarge(message) => {
+                write!(f, "{}", message)
+            }
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl error::Error for UnbundlerError {}
+
 impl From<&io::Error> for UnbundlerError {
     fn from(value: &io::Error) -> Self {
         UnbundlerError::IOError(format!("{}", value))
@@ -210,6 +327,11 @@ impl From<&io::Error> for UnbundlerError {
 
 impl From<io::Error> for UnbundlerError {
     fn from(value: io::Error) -> Self {
+        //errors raised while inflating are passed through io::Error by the Inflater's Read impl.
+        if value.get_ref().is_some_and(|e| e.is::<UnbundlerError>()) {
+            return *value.into_inner().unwrap().downcast().unwrap();
+        }
+
         UnbundlerError::IOError(format!("{}", value))
     }
 }
@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::file_filter::FileFilter;
+use crate::game_profile::GameProfile;
+use crate::unbundler::{Unbundler, UnbundlerError};
+
+pub struct Verifier {
+    pub file_path: PathBuf,
+    pub profile: Option<GameProfile>,
+}
+
+#[derive(Serialize)]
+pub struct BundleReport {
+    pub bundle_name: String,
+    pub passed: bool,
+    pub file_count: usize,
+    pub inflated_size: u32,
+    pub errors: Vec<String>,
+}
+
+impl Verifier {
+    pub fn verify(&self) -> Result<Vec<BundleReport>, UnbundlerError> {
+        let unbundler = Unbundler {
+            file_path: self.file_path.clone(),
+            filter: FileFilter::default(),
+            profile: self.profile.clone(),
+        };
+
+        let mut reports = vec![];
+        for bundle_path in unbundler.bundle_paths()? {
+            reports.push(Verifier::verify_bundle(&unbundler, &bundle_path));
+        }
+
+        Ok(reports)
+    }
+
+    fn verify_bundle(unbundler: &Unbundler, bundle_path: &Path) -> BundleReport {
+        let mut report = BundleReport {
+            bundle_name: bundle_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            passed: false,
+            file_count: 0,
+            inflated_size: 0,
+            errors: vec![],
+        };
+
+        let mut bundle_reader = match unbundler.read_bundle(bundle_path) {
+            Ok(bundle_reader) => bundle_reader,
+            Err(e) => {
+                report
+                    .errors
+                    .push(format!("Could not read the bundle header: {}", e));
+                return report;
+            }
+        };
+        let names = bundle_reader.header.names.clone();
+        report.file_count = names.len();
+        report.inflated_size = bundle_reader.header.inflated_size;
+
+        let mut files_read = 0;
+        for (index, unbundled_file) in bundle_reader.by_ref().enumerate() {
+            match unbundled_file {
+                Ok(file) => {
+                    if names[index] != (file.header.extension, file.path) {
+                        report.errors.push(format!(
+                            "File {} is {:#x}.{:#x} but the name table lists {:#x}.{:#x}.",
+                            index, file.path, file.header.extension, names[index].1, names[index].0
+                        ));
+                    }
+                    files_read += 1;
+                }
+                //the .stream file is checked once every file has been read.
+                Err(e) if index == names.len() => report
+                    .errors
+                    .push(format!("The .stream file does not match the bundle: {}", e)),
+                Err(e) => report
+                    .errors
+                    .push(format!("File {} could not be read: {}", index, e)),
+            }
+        }
+
+        if files_read != names.len() {
+            report.errors.push(format!(
+                "The name table lists {} files but {} could be read.",
+                names.len(),
+                files_read
+            ));
+            return Verifier::finish(report);
+        }
+
+        let files_end = bundle_reader.position();
+        let remaining = match bundle_reader.read_remaining() {
+            Ok(remaining) => remaining,
+            Err(e) => {
+                report
+                    .errors
+                    .push(format!("A zlib block could not be decoded: {}", e));
+                return Verifier::finish(report);
+            }
+        };
+
+        let declared = report.inflated_size as usize;
+        let inflated = files_end + remaining.len();
+        let block_size = bundle_reader.profile.block_size;
+        let padded = declared.div_ceil(block_size) * block_size;
+        if inflated != declared && inflated != padded {
+            report.errors.push(format!(
+                "The header declares {} inflated bytes but the stream inflates to {}.",
+                declared, inflated
+            ));
+        }
+
+        if files_end > declared {
+            report.errors.push(format!(
+                "The files end at {} which is past the declared size of {}.",
+                files_end, declared
+            ));
+        }
+
+        //anything between the last file and the declared size is left over data. the padding after it must be zero.
+        let unused = declared.saturating_sub(files_end).min(remaining.len());
+        let trailing = unused + Verifier::non_zero(&remaining[unused..]);
+        if trailing > 0 {
+            report.errors.push(format!(
+                "{} trailing bytes remain after the last file.",
+                trailing
+            ));
+        }
+
+        Verifier::finish(report)
+    }
+
+    fn non_zero(bytes: &[u8]) -> usize {
+        bytes.iter().filter(|byte| **byte != 0).count()
+    }
+
+    fn finish(mut report: BundleReport) -> BundleReport {
+        report.passed = report.errors.is_empty();
+        report
+    }
+}
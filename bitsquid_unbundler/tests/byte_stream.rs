@@ -0,0 +1,31 @@
+use std::io::{BufReader, Cursor};
+
+use bitsquid_unbundler::byte_stream::ByteStream;
+use bitsquid_unbundler::unbundler::UnbundlerError;
+
+#[test]
+fn peeking_fills_past_what_the_reader_has_buffered() {
+    let bytes: Vec<u8> = (0..32).collect();
+    let mut stream = ByteStream::from_reader(BufReader::with_capacity(3, Cursor::new(bytes)));
+
+    assert_eq!(stream.peek_bytes(10).unwrap(), (0..10).collect::<Vec<u8>>());
+    assert_eq!(stream.position(), 0);
+    assert_eq!(stream.read_uint().unwrap(), 0x03020100);
+    assert_eq!(stream.peek_byte().unwrap(), 4);
+    stream.skip(2).unwrap();
+    assert_eq!(stream.peek_bytes(20).unwrap(), (6..26).collect::<Vec<u8>>());
+    assert_eq!(stream.read(8).unwrap(), (6..14).collect::<Vec<u8>>());
+    assert_eq!(stream.position(), 14);
+
+    //only the end of the stream makes a peek fail, and nothing peeked is lost.
+    assert!(matches!(
+        stream.peek_bytes(19),
+        Err(UnbundlerError::UnexpectedEof {
+            offset: 14,
+            wanted: 19
+        })
+    ));
+    assert!(!stream.at_end().unwrap());
+    assert_eq!(stream.read_to_end().unwrap(), (14..32).collect::<Vec<u8>>());
+    assert!(stream.at_end().unwrap());
+}
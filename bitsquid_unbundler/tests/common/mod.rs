@@ -0,0 +1,152 @@
+//fixtures shared by the integration tests. each test binary only uses some of them.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use bitsquid_unbundler::bundle_header::{BundleEntryHeader, BundleHeader};
+use bitsquid_unbundler::bundler::Bundler;
+use bitsquid_unbundler::file_filter::FileFilter;
+use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use bitsquid_unbundler::unbundled_file::{ResourceVariant, UnbundledFile};
+use bitsquid_unbundler::unbundler::Unbundler;
+
+pub fn temp_path(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("bitsquid_{}_{}", name, std::process::id()));
+    path
+}
+
+//xorshift noise so that some blocks do not compress and are stored raw.
+pub fn noise(len: usize) -> Vec<u8> {
+    let mut state: u32 = 0x9e3779b9;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+pub fn sample_file(
+    extension: u64,
+    path: u64,
+    extension_name: &str,
+    variants: Vec<(u32, Vec<u8>)>,
+) -> UnbundledFile {
+    UnbundledFile {
+        path,
+        extension: extension_name.to_string(),
+        header: BundleEntryHeader {
+            extension,
+            path,
+            variant_count: variants.len() as u32,
+            reserved: 0,
+        },
+        variants: variants
+            .into_iter()
+            .map(|(language, data)| ResourceVariant {
+                language,
+                flags: 0x42 + language,
+                data,
+                stream: vec![],
+            })
+            .collect(),
+    }
+}
+
+pub fn sample_directory() -> UnbundledDirectory {
+    let files = vec![
+        sample_file(
+            0xa14e8dfa2cd117e2,
+            0x1a2b3c4d5e6f7081,
+            "lua",
+            vec![(0, b"return { hello = \"world\" }".to_vec())],
+        ),
+        sample_file(0xad9c6d9ed1e5e77a, 0x0000000000000002, "package", vec![]),
+        sample_file(
+            0xcd4238c6a0c69e32,
+            0xfedcba9876543210,
+            "texture",
+            vec![(0, noise(200_000))],
+        ),
+        sample_file(
+            0x0d972bab10b40fd3,
+            0x00000000deadbeef,
+            "strings",
+            vec![
+                (0, b"hello".to_vec()),
+                (1, b"bonjour".to_vec()),
+                (7, b"hallo".to_vec()),
+            ],
+        ),
+        sample_file(
+            1234567,
+            0x0123456789abcdef,
+            "0x000000000012d687",
+            vec![(0, vec![7u8; 100_000])],
+        ),
+    ];
+
+    let header = BundleHeader {
+        checksum: noise(256),
+        ..BundleHeader::default()
+    };
+    UnbundledDirectory::new("sample".to_string(), header, files)
+}
+
+pub fn bundle(file_path: &Path, unbundled_dir: &UnbundledDirectory) {
+    Bundler {
+        file_path: file_path.to_path_buf(),
+        profile: None,
+    }
+    .bundle(unbundled_dir)
+    .unwrap();
+}
+
+//an unbundler without a filter, which detects the profile from the bundle header.
+pub fn unbundler(file_path: &Path) -> Unbundler {
+    Unbundler {
+        file_path: file_path.to_path_buf(),
+        filter: FileFilter::default(),
+        profile: None,
+    }
+}
+
+pub fn unbundle(file_path: &Path) -> UnbundledDirectory {
+    let mut unbundled = unbundler(file_path).unbundle().unwrap();
+    assert_eq!(unbundled.len(), 1);
+    unbundled.remove(0)
+}
+
+pub fn assert_same_file(left: &UnbundledFile, right: &UnbundledFile) {
+    assert_eq!(left.path, right.path);
+    assert_eq!(left.extension, right.extension);
+    assert_eq!(left.header.extension, right.header.extension);
+    assert_eq!(left.header.variant_count, right.header.variant_count);
+    assert_eq!(left.header.reserved, right.header.reserved);
+    assert_eq!(left.variants.len(), right.variants.len());
+    for (l, r) in left.variants.iter().zip(right.variants.iter()) {
+        assert_eq!(l.language, r.language);
+        assert_eq!(l.data, r.data);
+    }
+}
+
+pub fn assert_same_files(left: &UnbundledDirectory, right: &UnbundledDirectory) {
+    assert_eq!(left.files.len(), right.files.len());
+    for (l, r) in left.files.iter().zip(right.files.iter()) {
+        assert_same_file(l, r);
+    }
+}
+
+pub fn assert_same_headers(left: &UnbundledDirectory, right: &UnbundledDirectory) {
+    assert_eq!(left.header.version, right.header.version);
+    assert_eq!(left.header.reserved, right.header.reserved);
+    assert_eq!(left.header.checksum, right.header.checksum);
+    for (l, r) in left.files.iter().zip(right.files.iter()) {
+        for (lv, rv) in l.variants.iter().zip(r.variants.iter()) {
+            assert_eq!(lv.flags, rv.flags);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::bundle_diff::{BundleDiff, ChangeKind};
+
+use common::{bundle, sample_directory, sample_file, temp_path, unbundler};
+
+#[test]
+fn diff_reports_added_removed_and_modified_resources() {
+    let old_path = temp_path("diff_old");
+    let new_path = temp_path("diff_new");
+
+    let old = sample_directory();
+    let mut new = sample_directory();
+    new.files[0].variants[0].data = b"return { hello = \"there\" }".to_vec();
+    new.files.remove(4);
+    new.files.push(sample_file(
+        0xa14e8dfa2cd117e2,
+        0x0000000000000042,
+        "lua",
+        vec![(0, b"return 42".to_vec())],
+    ));
+    bundle(&old_path, &old);
+    bundle(&new_path, &new);
+
+    let diff = BundleDiff::diff(&unbundler(&old_path), &unbundler(&new_path), 1).unwrap();
+
+    let changes: Vec<(u64, bool, bool, i64)> = diff
+        .changes
+        .iter()
+        .map(|change| {
+            (
+                change.path,
+                change.kind == ChangeKind::Added,
+                change.kind == ChangeKind::Removed,
+                change.size_delta,
+            )
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (0x0123456789abcdef, false, true, -100_000),
+            (0x42, true, false, 9),
+            (0x1a2b3c4d5e6f7081, false, false, 0),
+        ]
+    );
+
+    let bytes = diff.changes[2].bytes.as_ref().unwrap();
+    assert_eq!(bytes.first_difference, Some(18));
+    assert_eq!(bytes.differing_bytes, 5);
+
+    fs::remove_file(old_path).unwrap();
+    fs::remove_file(new_path).unwrap();
+}
@@ -0,0 +1,31 @@
+use bitsquid_unbundler::extension_registry::{ExtensionRegistry, TEXTURE};
+
+#[test]
+fn extension_registry_is_extended_from_text_and_records_unknown_hashes() {
+    let registry = ExtensionRegistry::embedded();
+    assert_eq!(registry.name(0xa14e8dfa2cd117e2), "lua");
+    assert_eq!(registry.name(TEXTURE), "texture");
+    assert_eq!(registry.hash("texture"), Some(TEXTURE));
+    //the file written for a texture is not a type of its own.
+    assert_eq!(registry.hash("dds"), None);
+
+    assert_eq!(registry.name(0x1122334455667788), "0x1122334455667788");
+    assert_eq!(registry.name(0x99), "0x0000000000000099");
+    assert_eq!(registry.hash("0x0000000000000099"), Some(0x99));
+    assert_eq!(registry.unknown(), vec![0x99, 0x1122334455667788]);
+    assert_eq!(
+        registry.unknown_table().lines().skip(1).collect::<Vec<_>>(),
+        vec!["0x0000000000000099", "0x1122334455667788"]
+    );
+
+    //the unknown table can be shared back with only some of the names filled in.
+    let shared = registry
+        .unknown_table()
+        .replace("0x1122334455667788", "0x1122334455667788 wwise_dep");
+    assert_eq!(registry.parse(&shared).unwrap(), 1);
+    assert_eq!(registry.name(0x1122334455667788), "wwise_dep");
+    assert_eq!(registry.hash("wwise_dep"), Some(0x1122334455667788));
+    assert_eq!(registry.hash("1234567"), None);
+
+    assert!(registry.parse("not_a_hash lua").is_err());
+}
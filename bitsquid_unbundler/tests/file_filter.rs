@@ -0,0 +1,149 @@
+mod common;
+
+use std::fs;
+use std::sync::Arc;
+
+use bitsquid_unbundler::bundle_header::BundleHeader;
+use bitsquid_unbundler::file_filter::FileFilter;
+use bitsquid_unbundler::game_profile::GameProfile;
+use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use bitsquid_unbundler::unbundler::Unbundler;
+use murmur32_gen::dictionary::Dictionary;
+use murmur32_gen::murmur::hash64;
+
+use common::{bundle, sample_directory, sample_file, temp_path, unbundler};
+
+#[test]
+fn filter_selects_files_by_type_hash_and_name() {
+    let bundle_path = temp_path("filter");
+    bundle(&bundle_path, &sample_directory());
+
+    let selected = |filter: FileFilter| {
+        let unbundler = Unbundler {
+            filter,
+            ..unbundler(&bundle_path)
+        };
+        let mut paths = vec![];
+        unbundler
+            .for_each_file(|_dir_name, file| paths.push(file.path))
+            .unwrap();
+        assert_eq!(unbundler.count_files().unwrap(), paths.len());
+        paths
+    };
+
+    let by_type = FileFilter {
+        extensions: FileFilter::parse_extensions("lua, strings", None).unwrap(),
+        ..FileFilter::default()
+    };
+    assert_eq!(selected(by_type), vec![0x1a2b3c4d5e6f7081, 0xdeadbeef]);
+
+    let by_hash = FileFilter {
+        paths: FileFilter::parse_paths("0xfedcba9876543210,2").unwrap(),
+        ..FileFilter::default()
+    };
+    assert_eq!(selected(by_hash), vec![0x2, 0xfedcba9876543210]);
+
+    let by_name = FileFilter {
+        names: vec!["**/0x*ef.*".to_string()],
+        ..FileFilter::default()
+    };
+    assert_eq!(selected(by_name), vec![0xdeadbeef, 0x0123456789abcdef]);
+
+    assert!(FileFilter::parse_extensions("not_a_type", None).is_err());
+    assert!(FileFilter::parse_paths("0xnothex").is_err());
+
+    fs::remove_file(bundle_path).unwrap();
+}
+
+#[test]
+fn name_globs_match_restored_names_first() {
+    let bundle_path = temp_path("filter_restored");
+    let player = hash64("scripts/game/player");
+    let files = vec![
+        sample_file(
+            0xa14e8dfa2cd117e2,
+            player,
+            "lua",
+            vec![(0, b"player".to_vec())],
+        ),
+        sample_file(
+            0xa14e8dfa2cd117e2,
+            0x1234,
+            "lua",
+            vec![(0, b"unknown".to_vec())],
+        ),
+    ];
+    bundle(
+        &bundle_path,
+        &UnbundledDirectory::new("restored".to_string(), BundleHeader::default(), files),
+    );
+
+    let mut dictionary = Dictionary::default();
+    dictionary.add("scripts/game/player");
+    let dictionary = Some(Arc::new(dictionary));
+    let selected = |glob: &str, dictionary: Option<Arc<Dictionary>>| {
+        let unbundler = Unbundler {
+            filter: FileFilter {
+                names: vec![glob.to_string()],
+                dictionary,
+                ..FileFilter::default()
+            },
+            ..unbundler(&bundle_path)
+        };
+        let mut paths = vec![];
+        unbundler
+            .for_each_file(|_dir_name, file| paths.push(file.path))
+            .unwrap();
+        paths
+    };
+
+    assert_eq!(
+        selected("scripts/**/*.lua", dictionary.clone()),
+        vec![player]
+    );
+    assert!(selected("scripts/**/*.lua", None).is_empty());
+    //a restored file is no longer matched by its hex name, unknown ones still are.
+    assert_eq!(selected("0x*.lua", dictionary.clone()), vec![0x1234]);
+    assert_eq!(selected("0x*.lua", None), vec![player, 0x1234]);
+
+    fs::remove_file(bundle_path).unwrap();
+}
+
+#[test]
+fn types_and_names_use_the_extensions_of_the_profile() {
+    let bundle_path = temp_path("filter_profile");
+    bundle(&bundle_path, &sample_directory());
+    let profile = GameProfile {
+        extensions: [(0xa14e8dfa2cd117e2, "luac".to_string())].into(),
+        ..GameProfile::default()
+    };
+
+    let selected = |filter: FileFilter| {
+        let unbundler = Unbundler {
+            filter,
+            profile: Some(profile.clone()),
+            ..unbundler(&bundle_path)
+        };
+        let mut paths = vec![];
+        unbundler
+            .for_each_file(|_dir_name, file| paths.push(file.path))
+            .unwrap();
+        paths
+    };
+
+    let by_type = FileFilter {
+        extensions: FileFilter::parse_extensions("luac", Some(&profile)).unwrap(),
+        ..FileFilter::default()
+    };
+    assert_eq!(selected(by_type), vec![0x1a2b3c4d5e6f7081]);
+    assert!(FileFilter::parse_extensions("luac", None).is_err());
+
+    let by_name = FileFilter {
+        names: vec!["*.luac".to_string()],
+        profile: Some(profile.clone()),
+        ..FileFilter::default()
+    };
+    assert_eq!(selected(by_name), vec![0x1a2b3c4d5e6f7081]);
+
+    fs::remove_file(bundle_path).unwrap();
+}
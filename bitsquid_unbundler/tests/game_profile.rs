@@ -0,0 +1,173 @@
+mod common;
+
+use std::fs;
+use std::io::Write;
+
+use bitsquid_unbundler::bundle_header::DEFAULT_VERSION;
+use bitsquid_unbundler::bundler::Bundler;
+use bitsquid_unbundler::game_profile::GameProfile;
+use bitsquid_unbundler::unbundler::{Unbundler, UnbundlerError};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use common::{
+    assert_same_files, assert_same_headers, bundle, sample_directory, temp_path, unbundle,
+    unbundler,
+};
+
+//a bundle with 24 byte name table entries which, unlike the bundler's, are not zero padded.
+fn wide_name_bundle(version: u32, data: &[u8]) -> Vec<u8> {
+    let mut inflated = vec![];
+    inflated.extend(1u32.to_le_bytes());
+    inflated.extend(vec![0u8; 256]);
+    inflated.extend(0xa14e8dfa2cd117e2u64.to_le_bytes());
+    inflated.extend(0x1234u64.to_le_bytes());
+    inflated.extend([0xffu8; 8]);
+    inflated.extend(0xa14e8dfa2cd117e2u64.to_le_bytes());
+    inflated.extend(0x1234u64.to_le_bytes());
+    inflated.extend(1u64.to_le_bytes());
+    inflated.extend(
+        [0u32, data.len() as u32, 0u32]
+            .map(u32::to_le_bytes)
+            .concat(),
+    );
+    inflated.extend(data);
+
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(&inflated).unwrap();
+    let block = encoder.finish().unwrap();
+
+    let mut bundle = vec![];
+    bundle.extend(version.to_le_bytes());
+    bundle.extend((inflated.len() as u32).to_le_bytes());
+    bundle.extend(0u32.to_le_bytes());
+    bundle.extend((block.len() as u32).to_le_bytes());
+    bundle.extend(block);
+    bundle
+}
+
+#[test]
+fn profile_is_detected_from_the_header_version_or_loaded_from_a_file() {
+    let bundle_path = temp_path("profile_bundle");
+    fs::write(&bundle_path, wide_name_bundle(0xf0000006, b"return 6")).unwrap();
+
+    let mut unbundler = unbundler(&bundle_path);
+    let bundle_reader = unbundler.read_bundle(&bundle_path).unwrap();
+    assert_eq!(bundle_reader.profile.name, "vermintide2");
+    assert_eq!(
+        bundle_reader.header.names,
+        vec![(0xa14e8dfa2cd117e2, 0x1234)]
+    );
+    let unbundled = unbundler.unbundle().unwrap();
+    assert_eq!(unbundled[0].files[0].extension, "lua");
+    assert_eq!(unbundled[0].files[0].variants[0].data, b"return 6");
+
+    let mut profile_path = temp_path("profile");
+    profile_path.set_extension("toml");
+    fs::write(
+        &profile_path,
+        "name = \"custom\"\nversions = [\"0xf0000006\"]\nname_entry_size = 24\nskipped_extensions = [\"stream\", \"patch\"]\n\n[extensions]\n\"0xa14e8dfa2cd117e2\" = \"luac\"\n",
+    )
+    .unwrap();
+    let profile = GameProfile::named(profile_path.to_str().unwrap()).unwrap();
+    assert_eq!(profile.versions, vec![0xf0000006]);
+    assert_eq!(profile.block_size, 1 << 16);
+    assert_eq!(profile.skipped_extensions, vec!["stream", "patch"]);
+
+    unbundler.profile = Some(profile);
+    let unbundled = unbundler.unbundle().unwrap();
+    assert_eq!(unbundled[0].files[0].extension, "luac");
+
+    let json_path = profile_path.with_extension("json");
+    fs::write(&json_path, r#"{ "name": "json", "checksum_size": 0 }"#).unwrap();
+    let profile = GameProfile::load(&json_path).unwrap();
+    assert_eq!(profile.checksum_size, 0);
+    assert_eq!(profile.name_entry_size, 16);
+
+    assert!(GameProfile::named("not_a_game").is_err());
+
+    fs::remove_file(bundle_path).unwrap();
+    fs::remove_file(profile_path).unwrap();
+    fs::remove_file(json_path).unwrap();
+}
+
+#[test]
+fn bundles_round_trip_with_every_built_in_profile() {
+    for profile in GameProfile::builtin() {
+        let bundle_path = temp_path(&format!("profile_round_trip_{}", profile.name));
+        let mut original = sample_directory();
+        original.header.version = profile.versions.first().copied().unwrap_or(DEFAULT_VERSION);
+
+        Bundler {
+            file_path: bundle_path.clone(),
+            profile: Some(profile.clone()),
+        }
+        .bundle(&original)
+        .unwrap();
+        let unbundled = Unbundler {
+            profile: Some(profile.clone()),
+            ..unbundler(&bundle_path)
+        }
+        .unbundle()
+        .unwrap()
+        .remove(0);
+        assert_same_files(&original, &unbundled);
+        assert_same_headers(&original, &unbundled);
+
+        //without a profile, both sides pick it from the header version.
+        if !profile.versions.is_empty() {
+            bundle(&bundle_path, &original);
+            let detected = unbundle(&bundle_path);
+            assert_same_files(&original, &detected);
+            assert_eq!(
+                unbundler(&bundle_path)
+                    .read_bundle(&bundle_path)
+                    .unwrap()
+                    .profile
+                    .name,
+                profile.name
+            );
+        }
+
+        fs::remove_file(bundle_path).unwrap();
+    }
+}
+
+#[test]
+fn profiles_without_streams_neither_read_nor_write_stream_files() {
+    let bundle_path = temp_path("profile_no_streams");
+    let profile = GameProfile {
+        name: "no_streams".to_string(),
+        streams: false,
+        ..GameProfile::default()
+    };
+    let mut directory = sample_directory();
+    directory.files[0].variants[0].stream = b"streamed".to_vec();
+
+    let bundler = Bundler {
+        file_path: bundle_path.clone(),
+        profile: Some(profile.clone()),
+    };
+    assert!(matches!(
+        bundler.bundle(&directory),
+        Err(UnbundlerError::Profile(_))
+    ));
+
+    //a .stream file beside the bundle is left alone.
+    bundle(&bundle_path, &directory);
+    let unbundled = Unbundler {
+        profile: Some(profile),
+        ..unbundler(&bundle_path)
+    }
+    .unbundle()
+    .unwrap()
+    .remove(0);
+    assert!(unbundled.files[0].variants[0].stream.is_empty());
+    assert_eq!(
+        unbundle(&bundle_path).files[0].variants[0].stream,
+        b"streamed"
+    );
+
+    fs::remove_file(Unbundler::stream_path(&bundle_path)).unwrap();
+    fs::remove_file(bundle_path).unwrap();
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::bundle_header::BundleHeader;
+use bitsquid_unbundler::file_filter::FileFilter;
+use bitsquid_unbundler::name_harvester::NameHarvester;
+use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use bitsquid_unbundler::unbundler::Unbundler;
+use murmur32_gen::murmur::hash64;
+
+use common::{bundle, noise, sample_file, temp_path, unbundler};
+
+#[test]
+fn harvester_finds_names_referenced_by_other_resources() {
+    let bundle_path = temp_path("harvest");
+    let player = hash64("scripts/game/player");
+    let hero = hash64("units/beings/hero");
+
+    let script = b"\x1bLJ\x02local p = require \"scripts.game.player\"\n\x15Unit.spawn(\"units\\beings\\Hero.unit\")\x00settings";
+    let files = vec![
+        sample_file(0xa14e8dfa2cd117e2, 1, "lua", vec![(0, script.to_vec())]),
+        sample_file(
+            0xa14e8dfa2cd117e2,
+            player,
+            "lua",
+            vec![(0, b"return".to_vec())],
+        ),
+        sample_file(0xe0a48d0be9a7453f, hero, "unit", vec![(0, noise(64))]),
+        sample_file(0x0d972bab10b40fd3, hero, "strings", vec![]),
+    ];
+    bundle(
+        &bundle_path,
+        &UnbundledDirectory::new("harvest".to_string(), BundleHeader::default(), files),
+    );
+
+    let unbundler = unbundler(&bundle_path);
+    let harvester = NameHarvester::new(&unbundler).unwrap();
+    for jobs in [1, 3] {
+        let names = harvester.harvest(&unbundler, jobs).unwrap();
+        let names: Vec<(u64, &str, Vec<String>)> = names
+            .iter()
+            .map(|name| (name.path, name.name.as_str(), name.extensions.clone()))
+            .collect();
+
+        let mut expected = vec![
+            (player, "scripts/game/player", vec!["lua".to_string()]),
+            (
+                hero,
+                "units/beings/hero",
+                vec!["strings".to_string(), "unit".to_string()],
+            ),
+        ];
+        expected.sort();
+        assert_eq!(names, expected);
+    }
+
+    //only lua files are searched, but they still find the names of the other types.
+    let lua_only = Unbundler {
+        filter: FileFilter {
+            extensions: FileFilter::parse_extensions("lua", None).unwrap(),
+            ..FileFilter::default()
+        },
+        ..unbundler
+    };
+    let names = NameHarvester::new(&lua_only)
+        .unwrap()
+        .harvest(&lua_only, 1)
+        .unwrap();
+    let mut expected = vec![player, hero];
+    expected.sort();
+    assert_eq!(
+        names.iter().map(|name| name.path).collect::<Vec<_>>(),
+        expected
+    );
+    assert_eq!(
+        names
+            .iter()
+            .find(|name| name.path == hero)
+            .unwrap()
+            .extensions,
+        vec!["strings", "unit"]
+    );
+
+    assert!(NameHarvester::candidates("./Units\\Hero.unit").contains("units/hero"));
+    assert_eq!(
+        NameHarvester::strings(b"ab\"core/shaders\"\x00xyzw").collect::<Vec<_>>(),
+        vec!["core/shaders", "xyzw"]
+    );
+
+    fs::remove_file(bundle_path).unwrap();
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::bundle_header::BundleHeader;
+use bitsquid_unbundler::extension_registry::{PACKAGE, TEXTURE};
+use bitsquid_unbundler::package::{Package, PackageGraph};
+use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use murmur32_gen::dictionary::Dictionary;
+use murmur32_gen::murmur::hash64;
+
+use common::{bundle, sample_file, temp_path, unbundler};
+
+#[test]
+fn packages_are_parsed_and_graphed_against_the_bundles() {
+    let dir_path = temp_path("packages");
+    fs::create_dir_all(&dir_path).unwrap();
+    let player = hash64("scripts/game/player");
+
+    let package = Package {
+        version: 0x2b,
+        entries: vec![
+            (0xa14e8dfa2cd117e2, player),
+            (0xe0a48d0be9a7453f, 0x77),
+            (TEXTURE, 0x99),
+        ],
+        trailer: vec![1, 0, 0, 0],
+    };
+    let bytes = package.to_bytes();
+    let parsed = Package::parse(&bytes).unwrap();
+    assert_eq!(parsed.entries, package.entries);
+    assert_eq!(parsed.to_bytes(), bytes);
+    assert!(Package::parse(&bytes[..20]).is_err());
+
+    let mut names = Dictionary::default();
+    names.add_all(["scripts/game/player", "resource_packages/level"]);
+    assert_eq!(
+        parsed.to_text(&names),
+        "lua scripts/game/player\nunit 0x77\ntexture 0x99\n"
+    );
+
+    let bundles = [
+        (
+            "a",
+            vec![
+                sample_file(
+                    PACKAGE,
+                    hash64("resource_packages/level"),
+                    "package",
+                    vec![(0, bytes.clone())],
+                ),
+                sample_file(0xa14e8dfa2cd117e2, player, "lua", vec![(0, vec![1])]),
+            ],
+        ),
+        (
+            "b",
+            vec![
+                sample_file(0xa14e8dfa2cd117e2, player, "lua", vec![(0, vec![1])]),
+                sample_file(0xe0a48d0be9a7453f, 0x77, "unit", vec![(0, vec![2])]),
+                //a broken package does not keep the others from being graphed.
+                sample_file(PACKAGE, 0x55, "package", vec![(0, bytes[..20].to_vec())]),
+            ],
+        ),
+    ];
+    for (name, files) in bundles {
+        bundle(
+            &dir_path.join(name),
+            &UnbundledDirectory::new(name.to_string(), BundleHeader::default(), files),
+        );
+    }
+
+    let graph = PackageGraph::build(&unbundler(&dir_path), &names, 2).unwrap();
+    assert_eq!(graph.packages.len(), 1);
+
+    let node = &graph.packages[0];
+    assert_eq!(node.name.as_deref(), Some("resource_packages/level"));
+    assert_eq!(node.bundles, vec!["a".to_string()]);
+    assert_eq!(node.entries.len(), 3);
+    assert_eq!(node.dependencies.get("a"), Some(&1));
+    assert_eq!(node.dependencies.get("b"), Some(&2));
+    assert_eq!(node.missing, vec!["texture 0x99".to_string()]);
+
+    fs::remove_dir_all(dir_path).unwrap();
+}
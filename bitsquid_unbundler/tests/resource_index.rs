@@ -0,0 +1,58 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::file_filter::FileFilter;
+use bitsquid_unbundler::resource_index::ResourceIndex;
+
+use common::{bundle, sample_directory, temp_path, unbundler};
+
+#[test]
+fn resource_index_finds_every_copy_and_flags_differences() {
+    let dir_path = temp_path("where");
+    fs::create_dir_all(&dir_path).unwrap();
+
+    for (name, lua) in [("a", "return 1"), ("b", "return 1"), ("c", "return 2")] {
+        let mut directory = sample_directory();
+        directory.files[0].variants[0].data = lua.as_bytes().to_vec();
+        if name == "c" {
+            //the texture is only in the first two bundles.
+            directory.files.remove(2);
+        }
+        bundle(&dir_path.join(name), &directory);
+    }
+
+    let mut index_path = dir_path.clone();
+    index_path.set_extension("json");
+    ResourceIndex::build(&unbundler(&dir_path), 2)
+        .unwrap()
+        .save(&index_path)
+        .unwrap();
+    let index = ResourceIndex::load(&index_path).unwrap();
+
+    let lua = index.select(&FileFilter {
+        paths: vec![0x1a2b3c4d5e6f7081],
+        ..FileFilter::default()
+    });
+    assert_eq!(lua.len(), 1);
+    let bundles: Vec<&str> = lua[0]
+        .copies
+        .iter()
+        .map(|c| c.bundle_name.as_str())
+        .collect();
+    assert_eq!(bundles, vec!["a", "b", "c"]);
+    assert_eq!(lua[0].copies[0].digest, lua[0].copies[1].digest);
+    assert!(!lua[0].identical());
+
+    let textures = index.select(&FileFilter {
+        extensions: FileFilter::parse_extensions("texture", None).unwrap(),
+        ..FileFilter::default()
+    });
+    assert_eq!(textures[0].copies.len(), 2);
+
+    let mismatched: Vec<u64> = index.mismatched().iter().map(|r| r.path).collect();
+    assert_eq!(mismatched, vec![0x1a2b3c4d5e6f7081]);
+
+    fs::remove_dir_all(dir_path).unwrap();
+    fs::remove_file(index_path).unwrap();
+}
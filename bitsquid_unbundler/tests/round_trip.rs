@@ -0,0 +1,270 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::bundle_header::BundleHeader;
+use bitsquid_unbundler::bundle_manifest::{BundleManifest, MANIFEST_NAME};
+use bitsquid_unbundler::bundler::Bundler;
+use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use bitsquid_unbundler::unbundler::UnbundlerError;
+use murmur32_gen::murmur::hash64;
+
+use common::{
+    assert_same_file, assert_same_files, assert_same_headers, bundle, sample_directory, temp_path,
+    unbundle, unbundler,
+};
+
+#[test]
+fn unbundle_bundle_unbundle_is_identical() {
+    let first_path = temp_path("round_trip_first");
+    let second_path = temp_path("round_trip_second");
+    let original = sample_directory();
+
+    bundle(&first_path, &original);
+    let first = unbundle(&first_path);
+    assert_same_files(&original, &first);
+    assert_same_headers(&original, &first);
+
+    let names: Vec<(u64, u64)> = original
+        .files
+        .iter()
+        .map(|file| (file.header.extension, file.path))
+        .collect();
+    assert_eq!(first.header.names, names);
+    assert!(first.header.inflated_size as usize > 300_000);
+
+    bundle(&second_path, &first);
+    let second = unbundle(&second_path);
+    assert_same_files(&first, &second);
+    assert_same_headers(&first, &second);
+    assert_eq!(first.header.inflated_size, second.header.inflated_size);
+
+    fs::remove_file(first_path).unwrap();
+    fs::remove_file(second_path).unwrap();
+}
+
+#[test]
+fn bundles_directory_written_by_file_writer() {
+    let dir_path = temp_path("round_trip_dir");
+    let bundle_path = temp_path("round_trip_dir_bundle");
+    let original = sample_directory();
+
+    fs::create_dir_all(&dir_path).unwrap();
+    for file in original.files.iter() {
+        if file.variants.is_empty() {
+            let mut path = dir_path.clone();
+            path.push(format!("{:#x}.{}", file.path, file.extension));
+            fs::write(path, []).unwrap();
+        }
+
+        for variant in file.variants.iter() {
+            let mut path = dir_path.clone();
+            path.push(file.variant_file_name(&file.hex_name(), variant, &file.extension));
+            fs::write(path, &variant.data).unwrap();
+        }
+    }
+
+    Bundler {
+        file_path: bundle_path.clone(),
+        profile: None,
+    }
+    .bundle_directory(&dir_path)
+    .unwrap();
+    let unbundled = unbundle(&bundle_path);
+
+    //the directory is read back in file name order, so match the files by path.
+    assert_eq!(original.files.len(), unbundled.files.len());
+    for file in original.files.iter() {
+        let read_back = unbundled
+            .files
+            .iter()
+            .find(|read_back| read_back.path == file.path)
+            .unwrap();
+        assert_same_file(file, read_back);
+    }
+
+    fs::remove_dir_all(dir_path).unwrap();
+    fs::remove_file(bundle_path).unwrap();
+}
+
+#[test]
+fn manifest_restores_the_header_order_and_flags() {
+    let dir_path = temp_path("round_trip_manifest");
+    let bundle_path = temp_path("round_trip_manifest_bundle");
+    let mut original = sample_directory();
+    original.header.version = 0xf0000006;
+    original.header.reserved = 3;
+
+    fs::create_dir_all(&dir_path).unwrap();
+    let mut manifest = BundleManifest::default();
+    manifest.set_header(&original.header);
+    for file in original.files.iter() {
+        manifest.push(file);
+        if file.variants.is_empty() {
+            fs::write(
+                dir_path.join(format!("{:#x}.{}", file.path, file.extension)),
+                [],
+            )
+            .unwrap();
+        }
+        for variant in file.variants.iter() {
+            let name = file.variant_file_name(&file.hex_name(), variant, &file.extension);
+            fs::write(dir_path.join(name), &variant.data).unwrap();
+        }
+    }
+    manifest.save(&dir_path.join(MANIFEST_NAME)).unwrap();
+
+    Bundler {
+        file_path: bundle_path.clone(),
+        profile: None,
+    }
+    .bundle_directory(&dir_path)
+    .unwrap();
+    let unbundled = unbundle(&bundle_path);
+    assert_same_files(&original, &unbundled);
+    assert_same_headers(&original, &unbundled);
+
+    //a broken manifest is an error rather than a bundle with a default header.
+    fs::write(dir_path.join(MANIFEST_NAME), "{ \"checksum\": \"xyz\" }").unwrap();
+    assert!(matches!(
+        Bundler::read_directory(&dir_path),
+        Err(UnbundlerError::Json(_))
+    ));
+
+    fs::remove_dir_all(dir_path).unwrap();
+    fs::remove_file(bundle_path).unwrap();
+}
+
+#[test]
+fn bundles_directory_with_restored_names() {
+    let dir_path = temp_path("round_trip_restored");
+    let bundle_path = temp_path("round_trip_restored_bundle");
+
+    fs::create_dir_all(dir_path.join("scripts/game.v2")).unwrap();
+    fs::write(dir_path.join("scripts/game.v2/player.lua"), b"player").unwrap();
+    fs::write(
+        dir_path.join("scripts/game.v2/player.lang_03.lua"),
+        b"joueur",
+    )
+    .unwrap();
+    fs::write(dir_path.join("settings.strings"), b"settings").unwrap();
+    fs::write(dir_path.join("0x1a2b3c4d5e6f7081.lua"), b"unknown").unwrap();
+    //names restored with dots in them keep them, with or without a language.
+    fs::write(dir_path.join("foo.bar.lua"), b"dotted").unwrap();
+    fs::write(dir_path.join("foo.bar.lang_02.lua"), b"pointe").unwrap();
+
+    Bundler {
+        file_path: bundle_path.clone(),
+        profile: None,
+    }
+    .bundle_directory(&dir_path)
+    .unwrap();
+    let unbundled = unbundle(&bundle_path);
+
+    let mut paths: Vec<(u64, usize)> = unbundled
+        .files
+        .iter()
+        .map(|file| (file.path, file.variants.len()))
+        .collect();
+    paths.sort();
+    let mut expected = vec![
+        (hash64("scripts/game.v2/player"), 2),
+        (hash64("settings"), 1),
+        (hash64("foo.bar"), 2),
+        (0x1a2b3c4d5e6f7081, 1),
+    ];
+    expected.sort();
+    assert_eq!(paths, expected);
+    let dotted = unbundled
+        .files
+        .iter()
+        .find(|file| file.path == hash64("foo.bar"))
+        .unwrap();
+    assert_eq!(dotted.extension, "lua");
+
+    fs::remove_dir_all(dir_path).unwrap();
+    fs::remove_file(bundle_path).unwrap();
+}
+
+#[test]
+fn for_each_file_streams_every_file() {
+    let bundle_path = temp_path("round_trip_streamed");
+    let original = sample_directory();
+    bundle(&bundle_path, &original);
+
+    let unbundler = unbundler(&bundle_path);
+    assert_eq!(unbundler.count_files().unwrap(), original.files.len());
+
+    let mut streamed = vec![];
+    unbundler
+        .for_each_file(|dir_name, file| {
+            assert_eq!(dir_name, bundle_path.file_name().unwrap().to_str().unwrap());
+            streamed.push(file);
+        })
+        .unwrap();
+    assert_same_files(
+        &original,
+        &UnbundledDirectory::new("sample".to_string(), BundleHeader::default(), streamed),
+    );
+
+    fs::remove_file(bundle_path).unwrap();
+}
+
+#[test]
+fn parallel_unbundling_visits_the_same_files_for_any_job_count() {
+    let dir_path = temp_path("round_trip_parallel");
+    fs::create_dir_all(&dir_path).unwrap();
+
+    for i in 0..5 {
+        bundle(&dir_path.join(format!("bundle_{}", i)), &sample_directory());
+    }
+
+    let unbundler = unbundler(&dir_path);
+    let visited_with = |jobs: usize| {
+        let visited = std::sync::Mutex::new(vec![]);
+        unbundler
+            .for_each_file_parallel(jobs, |dir_name, file| {
+                let sizes: Vec<usize> = file.variants.iter().map(|v| v.data.len()).collect();
+                visited
+                    .lock()
+                    .unwrap()
+                    .push((dir_name.to_string(), file.path, sizes));
+            })
+            .unwrap();
+        let mut visited = visited.into_inner().unwrap();
+        visited.sort();
+        visited
+    };
+
+    let serial = visited_with(1);
+    assert_eq!(serial.len(), 5 * sample_directory().files.len());
+    assert_eq!(serial, visited_with(4));
+
+    fs::remove_dir_all(dir_path).unwrap();
+}
+
+#[test]
+fn truncated_bundle_is_reported_and_skipped() {
+    let dir_path = temp_path("round_trip_truncated");
+    fs::create_dir_all(&dir_path).unwrap();
+
+    bundle(&dir_path.join("good"), &sample_directory());
+    let bad_path = dir_path.join("bad");
+    let good = fs::read(dir_path.join("good")).unwrap();
+    fs::write(&bad_path, &good[..good.len() / 2]).unwrap();
+
+    //a single bad bundle is an error that knows where the stream ended.
+    match unbundler(&bad_path).unbundle() {
+        Err(UnbundlerError::Inflater(message)) => {
+            assert!(message.contains("Unexpected end of stream"))
+        }
+        _ => panic!("expected the truncated bundle to fail"),
+    }
+
+    //in a directory it is skipped and the good bundle is still unbundled.
+    let unbundled = unbundler(&dir_path).unbundle().unwrap();
+    assert_eq!(unbundled.len(), 1);
+    assert_eq!(unbundled[0].dir_name, "good");
+
+    fs::remove_dir_all(dir_path).unwrap();
+}
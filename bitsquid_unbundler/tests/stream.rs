@@ -0,0 +1,130 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::bundle_header::BundleHeader;
+use bitsquid_unbundler::bundler::Bundler;
+use bitsquid_unbundler::extension_registry::TEXTURE;
+use bitsquid_unbundler::file_filter::FileFilter;
+use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use bitsquid_unbundler::unbundler::{Unbundler, UnbundlerError};
+
+use common::{assert_same_file, bundle, noise, sample_file, temp_path, unbundle, unbundler};
+
+#[test]
+fn streamed_data_is_read_from_the_stream_file_and_written_back() {
+    let bundle_path = temp_path("streams");
+    let dir_path = temp_path("streams_dir");
+    let rebuilt_path = temp_path("streams_rebuilt");
+
+    let mut files = vec![
+        sample_file(TEXTURE, 1, "texture", vec![(0, b"header".to_vec())]),
+        sample_file(0xa14e8dfa2cd117e2, 2, "lua", vec![(0, b"return".to_vec())]),
+        sample_file(
+            0x0d972bab10b40fd3,
+            3,
+            "strings",
+            vec![(0, b"hi".to_vec()), (2, b"salut".to_vec())],
+        ),
+    ];
+    files[0].variants[0].stream = noise(5000);
+    files[2].variants[0].stream = b"first".to_vec();
+    files[2].variants[1].stream = b"second language".to_vec();
+    let original = UnbundledDirectory::new("streams".to_string(), BundleHeader::default(), files);
+
+    bundle(&bundle_path, &original);
+    let stream_path = Unbundler::stream_path(&bundle_path);
+    assert_eq!(fs::metadata(&stream_path).unwrap().len(), 5000 + 5 + 15);
+
+    let unbundled = unbundle(&bundle_path);
+    for (file, read_back) in original.files.iter().zip(unbundled.files.iter()) {
+        assert_same_file(file, read_back);
+        for (variant, read_back) in file.variants.iter().zip(read_back.variants.iter()) {
+            assert_eq!(variant.stream, read_back.stream);
+        }
+    }
+
+    //skipped files still move the offset into the stream file along.
+    let filtered = Unbundler {
+        filter: FileFilter {
+            extensions: vec![0x0d972bab10b40fd3],
+            ..FileFilter::default()
+        },
+        ..unbundler(&bundle_path)
+    };
+    let strings = filtered.unbundle().unwrap().remove(0).files.remove(0);
+    assert_eq!(strings.variants[1].stream, b"second language");
+
+    //the file writer's companion files are read back by the bundler.
+    fs::create_dir_all(&dir_path).unwrap();
+    for file in unbundled.files.iter() {
+        for variant in file.variants.iter() {
+            let name = file.variant_file_name(&file.hex_name(), variant, &file.extension);
+            fs::write(dir_path.join(&name), &variant.data).unwrap();
+            if !variant.stream.is_empty() {
+                fs::write(dir_path.join(format!("{}.stream", name)), &variant.stream).unwrap();
+            }
+        }
+    }
+    Bundler {
+        file_path: rebuilt_path.clone(),
+        profile: None,
+    }
+    .bundle_directory(&dir_path)
+    .unwrap();
+    let rebuilt = unbundle(&rebuilt_path);
+    let texture = rebuilt.files.iter().find(|file| file.path == 1).unwrap();
+    assert_eq!(texture.variants[0].stream, noise(5000));
+    assert_eq!(texture.variants[0].flags, 5000);
+    assert_eq!(
+        fs::read(Unbundler::stream_path(&rebuilt_path))
+            .unwrap()
+            .len(),
+        5020
+    );
+
+    for path in [&bundle_path, &rebuilt_path] {
+        fs::remove_file(Unbundler::stream_path(path)).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+    fs::remove_dir_all(dir_path).unwrap();
+}
+
+#[test]
+fn stream_files_not_laid_out_in_bundle_order_are_errors() {
+    let bundle_path = temp_path("streams_out_of_order");
+    let mut files = vec![
+        sample_file(TEXTURE, 1, "texture", vec![(0, b"first".to_vec())]),
+        sample_file(TEXTURE, 2, "texture", vec![(0, b"second".to_vec())]),
+    ];
+    files[0].variants[0].stream = b"aaaa".to_vec();
+    files[1].variants[0].stream = b"bbbbbbbb".to_vec();
+    bundle(
+        &bundle_path,
+        &UnbundledDirectory::new("ordered".to_string(), BundleHeader::default(), files),
+    );
+    let stream_path = Unbundler::stream_path(&bundle_path);
+
+    //the second variant's data first, with padding after the first, as another layout could have it.
+    fs::write(&stream_path, b"bbbbbbbbaaaa\0\0\0\0").unwrap();
+    match unbundler(&bundle_path).unbundle() {
+        Err(UnbundlerError::Stream(message)) => assert!(message.contains("16 bytes")),
+        _ => panic!("expected the stream file to be rejected"),
+    }
+
+    //the same holds when the files are skipped by a filter or visited one at a time.
+    let filtered = Unbundler {
+        filter: FileFilter {
+            paths: vec![1],
+            ..FileFilter::default()
+        },
+        ..unbundler(&bundle_path)
+    };
+    assert!(matches!(
+        filtered.for_each_file(|_, _| {}),
+        Err(UnbundlerError::Stream(_))
+    ));
+
+    fs::remove_file(stream_path).unwrap();
+    fs::remove_file(bundle_path).unwrap();
+}
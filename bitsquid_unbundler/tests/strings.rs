@@ -0,0 +1,151 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::bundler::Bundler;
+use bitsquid_unbundler::extension_registry::STRINGS;
+use bitsquid_unbundler::strings::{StringTable, StringsFormat};
+use bitsquid_unbundler::unbundler::UnbundlerError;
+use murmur32_gen::dictionary::Dictionary;
+use murmur32_gen::murmur::hash32;
+
+use common::temp_path;
+
+fn table() -> StringTable {
+    StringTable {
+        version: 3,
+        entries: vec![
+            (hash32("menu_start"), "Start".to_string()),
+            (
+                hash32("menu_quote"),
+                "He said \"run\", then\nran.".to_string(),
+            ),
+            (0xdeadbeef, "Grüße".to_string()),
+        ],
+    }
+}
+
+#[test]
+fn tables_are_written_back_byte_for_byte() {
+    let bytes = table().to_bytes();
+    assert_eq!(&bytes[0..8], &[3, 0, 0, 0, 3, 0, 0, 0]);
+    //the second value starts after "Start" and its terminator.
+    assert_eq!(&bytes[20..24], &6u32.to_le_bytes());
+
+    let parsed = StringTable::parse(&bytes).unwrap();
+    assert_eq!(parsed.version, 3);
+    assert_eq!(parsed.entries, table().entries);
+    assert_eq!(parsed.to_bytes(), bytes);
+
+    //an offset past the values is an error, not a panic.
+    let mut bad = bytes.clone();
+    bad[12..16].copy_from_slice(&1000u32.to_le_bytes());
+    assert!(matches!(
+        StringTable::parse(&bad),
+        Err(UnbundlerError::Strings(_))
+    ));
+}
+
+#[test]
+fn exports_resolve_names_and_import_back() {
+    let mut names = Dictionary::default();
+    names.add("menu_start");
+    names.add("menu_quote");
+
+    for format in [StringsFormat::Csv, StringsFormat::Json] {
+        let text = table().export(format, &names);
+        assert!(text.contains("menu_start"));
+        assert!(text.contains("0xdeadbeef"));
+
+        let mut imported = StringTable {
+            version: 3,
+            entries: vec![],
+        };
+        imported.import(format, &text).unwrap();
+        assert_eq!(imported.entries, table().entries);
+    }
+
+    let csv = table().export(StringsFormat::Csv, &names);
+    assert!(csv.starts_with("key,name,value\n"));
+    assert!(csv.contains("\"He said \"\"run\"\", then\nran.\""));
+}
+
+#[test]
+fn rows_without_a_key_are_hashed_from_their_name() {
+    let mut imported = table();
+    imported
+        .import(
+            StringsFormat::Csv,
+            "value,name\r\nContinue,menu_continue\r\n\"Quit, now\",menu_quit\r\n",
+        )
+        .unwrap();
+    assert_eq!(
+        imported.entries,
+        vec![
+            (hash32("menu_continue"), "Continue".to_string()),
+            (hash32("menu_quit"), "Quit, now".to_string()),
+        ]
+    );
+
+    imported
+        .import(
+            StringsFormat::Json,
+            r#"[{"name": "menu_back", "value": "Back"}]"#,
+        )
+        .unwrap();
+    assert_eq!(
+        imported.entries,
+        vec![(hash32("menu_back"), "Back".to_string())]
+    );
+
+    assert!(imported
+        .import(StringsFormat::Csv, "key,name,value\n,,Orphan\n")
+        .is_err());
+    assert!(imported
+        .import(StringsFormat::Csv, "key,value\n0x1,\"unclosed\n")
+        .is_err());
+}
+
+#[test]
+fn edited_tables_replace_the_strings_beside_them() {
+    let dir_path = temp_path("strings_import");
+    let _ = fs::remove_dir_all(&dir_path);
+    fs::create_dir_all(&dir_path).unwrap();
+    fs::write(
+        dir_path.join("0x0000000000000001.lang_02.strings"),
+        table().to_bytes(),
+    )
+    .unwrap();
+    fs::write(
+        dir_path.join("0x0000000000000001.lang_02.csv"),
+        "key,name,value\n,menu_start,Begin\n0xdeadbeef,,Hallo\n",
+    )
+    .unwrap();
+
+    let unbundled_dir = Bundler::read_directory(&dir_path).unwrap();
+    assert_eq!(unbundled_dir.files.len(), 1);
+    let file = &unbundled_dir.files[0];
+    assert_eq!(file.header.extension, STRINGS);
+    let imported = StringTable::parse(&file.variants[0].data).unwrap();
+    assert_eq!(imported.version, 3);
+    assert_eq!(
+        imported.entries,
+        vec![
+            (hash32("menu_start"), "Begin".to_string()),
+            (0xdeadbeef, "Hallo".to_string()),
+        ]
+    );
+
+    //a table of a language the bundle does not have is an error.
+    fs::write(
+        dir_path.join("0x0000000000000001.lang_03.json"),
+        r#"[{"key": "0x1", "value": "x"}]"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        Bundler::read_directory(&dir_path),
+        Err(UnbundlerError::Strings(_))
+    ));
+
+    fs::remove_dir_all(&dir_path).unwrap();
+}
@@ -0,0 +1,617 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::bcn::{decode, encode};
+use bitsquid_unbundler::bundler::Bundler;
+use bitsquid_unbundler::extension_registry::TEXTURE;
+use bitsquid_unbundler::texture::{Texture, TextureFormat};
+use bitsquid_unbundler::unbundler::{Unbundler, UnbundlerError};
+
+use common::{temp_path, unbundle};
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+//a minimal legacy dds header like the ones found inside texture resources.
+fn dds_header(width: u32, height: u32, mip_count: u32, four_cc: &[u8; 4], caps2: u32) -> Vec<u8> {
+    let mut header = vec![0u8; 128];
+    header[0..4].copy_from_slice(b"DDS ");
+    header[4..8].copy_from_slice(&124u32.to_le_bytes());
+    header[8..12].copy_from_slice(&0x21007u32.to_le_bytes());
+    header[12..16].copy_from_slice(&height.to_le_bytes());
+    header[16..20].copy_from_slice(&width.to_le_bytes());
+    header[28..32].copy_from_slice(&mip_count.to_le_bytes());
+    header[76..80].copy_from_slice(&32u32.to_le_bytes());
+    header[80..84].copy_from_slice(&4u32.to_le_bytes());
+    header[84..88].copy_from_slice(four_cc);
+    header[112..116].copy_from_slice(&caps2.to_le_bytes());
+    header
+}
+
+//flags, the embedded dds and (offset, size) references into the streamed data.
+fn texture_resource(dds: &[u8], stream_references: &[(u32, u32)]) -> Vec<u8> {
+    let mut resource = vec![];
+    resource.extend(7u32.to_le_bytes());
+    resource.extend((dds.len() as u32).to_le_bytes());
+    resource.extend(dds);
+    resource.extend((stream_references.len() as u32).to_le_bytes());
+    for (offset, size) in stream_references {
+        resource.extend(offset.to_le_bytes());
+        resource.extend(size.to_le_bytes());
+    }
+    resource
+}
+
+#[test]
+fn streamed_mips_are_joined_with_the_embedded_ones() {
+    //16x8 bc1 has mips of 64, 16 and 8 bytes. the largest is in the stream after 5 unrelated bytes.
+    let mut dds = dds_header(16, 8, 3, b"DXT1", 0);
+    dds.extend([2u8; 16]);
+    dds.extend([3u8; 8]);
+    let resource = texture_resource(&dds, &[(5, 64)]);
+    let mut stream = vec![0xffu8; 5];
+    stream.extend([1u8; 64]);
+
+    let texture = Texture::parse(&resource, &stream).unwrap();
+    assert_eq!(texture.flags, 7);
+    assert_eq!(texture.format, TextureFormat::Bc1);
+    assert_eq!((texture.width, texture.height, texture.depth), (16, 8, 1));
+    assert_eq!(texture.mip_count, 3);
+    assert!(!texture.cubemap);
+    assert_eq!(texture.data.len(), 88);
+    assert_eq!(&texture.data[..64], &[1u8; 64]);
+    assert_eq!(&texture.data[64..80], &[2u8; 16]);
+    assert_eq!(&texture.data[80..], &[3u8; 8]);
+
+    let output = texture.to_dds();
+    assert_eq!(&output[0..4], b"DDS ");
+    assert_eq!(u32_at(&output, 4), 124);
+    //caps, height, width, pixel format, mip count and linear size.
+    assert_eq!(
+        u32_at(&output, 8),
+        0x1 | 0x2 | 0x4 | 0x1000 | 0x20000 | 0x80000
+    );
+    assert_eq!((u32_at(&output, 12), u32_at(&output, 16)), (8, 16));
+    assert_eq!(u32_at(&output, 20), 64);
+    assert_eq!(u32_at(&output, 28), 3);
+    assert_eq!(u32_at(&output, 76), 32);
+    assert_eq!(u32_at(&output, 80), 0x4);
+    assert_eq!(&output[84..88], b"DXT1");
+    assert_eq!(u32_at(&output, 108), 0x1000 | 0x8 | 0x400000);
+    assert_eq!(u32_at(&output, 112), 0);
+    assert_eq!(output.len(), 128 + 88);
+    assert_eq!(&output[128..], &texture.data[..]);
+
+    //a texture converted to dds and back into a resource keeps every mip.
+    let reimported = Texture::parse(&texture.to_resource(), &[]).unwrap();
+    assert_eq!(reimported.flags, 7);
+    assert_eq!(reimported.data, texture.data);
+    assert_eq!(Texture::from_dds(&output).unwrap().data, texture.data);
+}
+
+#[test]
+fn cubemaps_stream_the_largest_mip_of_every_face() {
+    //8x8 bc3 has mips of 64, 16 and 16 bytes per face.
+    let mut dds = dds_header(8, 8, 3, b"DXT5", 0x200 | 0xfc00);
+    let mut stream = vec![];
+    let mut references = vec![];
+    for face in 0..6u8 {
+        dds.extend([face + 10; 32]);
+        references.push((stream.len() as u32, 64));
+        stream.extend([face; 64]);
+    }
+    let resource = texture_resource(&dds, &references);
+
+    let texture = Texture::parse(&resource, &stream).unwrap();
+    assert!(texture.cubemap);
+    assert_eq!(texture.data.len(), 6 * 96);
+    for face in 0..6u8 {
+        let surfaces = &texture.data[face as usize * 96..(face as usize + 1) * 96];
+        assert_eq!(&surfaces[..64], &[face; 64]);
+        assert_eq!(&surfaces[64..], &[face + 10; 32]);
+    }
+
+    let output = texture.to_dds();
+    assert_eq!(u32_at(&output, 112), 0x200 | 0xfc00);
+    assert_eq!(u32_at(&output, 108) & 0x8, 0x8);
+    assert_eq!(output.len(), 128 + 6 * 96);
+}
+
+#[test]
+fn formats_without_a_four_cc_get_a_dx10_header() {
+    //bc7 cubemap from a dds with a DX10 header: dxgi format, 2d, cube flag, array size.
+    let mut dds = dds_header(4, 4, 1, b"DX10", 0);
+    for value in [99u32, 3, 0x4, 1, 0] {
+        dds.extend(value.to_le_bytes());
+    }
+    dds.extend([9u8; 6 * 16]);
+
+    let texture = Texture::from_dds(&dds).unwrap();
+    assert_eq!(texture.format, TextureFormat::Bc7);
+    assert!(texture.cubemap);
+    assert_eq!(texture.mip_count, 1);
+
+    let output = texture.to_dds();
+    assert_eq!(&output[84..88], b"DX10");
+    assert_eq!(u32_at(&output, 8) & 0x20000, 0);
+    assert_eq!(u32_at(&output, 128), 98);
+    assert_eq!(u32_at(&output, 132), 3);
+    assert_eq!(u32_at(&output, 136), 0x4);
+    assert_eq!(u32_at(&output, 140), 1);
+    assert_eq!(output.len(), 148 + 6 * 16);
+}
+
+#[test]
+fn volume_textures_are_written_with_their_depth() {
+    let texture = Texture {
+        flags: 0,
+        format: TextureFormat::Rgba8,
+        width: 4,
+        height: 2,
+        depth: 4,
+        mip_count: 2,
+        cubemap: false,
+        data: vec![5u8; 4 * 2 * 4 * 4 + 2 * 2 * 4], //4x2x4 and 2x1x2 pixels.
+    };
+
+    let output = texture.to_dds();
+    assert_eq!(u32_at(&output, 8) & (0x800000 | 0x8), 0x800000 | 0x8);
+    assert_eq!(u32_at(&output, 20), 16); //pitch
+    assert_eq!(u32_at(&output, 24), 4);
+    assert_eq!(u32_at(&output, 80), 0x40 | 0x1);
+    assert_eq!(u32_at(&output, 88), 32);
+    assert_eq!(
+        [88, 92, 96, 100, 104].map(|offset| u32_at(&output, offset)),
+        [32, 0xff, 0xff00, 0xff0000, 0xff000000]
+    );
+    assert_eq!(u32_at(&output, 112), 0x200000);
+
+    let reread = Texture::from_dds(&output).unwrap();
+    assert_eq!((reread.width, reread.height, reread.depth), (4, 2, 4));
+    assert_eq!(reread.format, TextureFormat::Rgba8);
+    assert_eq!(reread.data, texture.data);
+}
+
+#[test]
+fn bad_stream_references_and_missing_headers_are_errors() {
+    let mut dds = dds_header(16, 8, 3, b"DXT1", 0);
+    dds.extend([0u8; 24]);
+
+    //the streamed mip must be exactly as large as the header says.
+    let resource = texture_resource(&dds, &[(0, 32)]);
+    assert!(matches!(
+        Texture::parse(&resource, &[0u8; 64]),
+        Err(UnbundlerError::Texture(_))
+    ));
+
+    //the stream must contain the referenced bytes.
+    let resource = texture_resource(&dds, &[(0, 64)]);
+    assert!(matches!(
+        Texture::parse(&resource, &[0u8; 10]),
+        Err(UnbundlerError::Texture(_))
+    ));
+
+    assert!(matches!(
+        Texture::parse(&texture_resource(b"not a dds", &[]), &[]),
+        Err(UnbundlerError::Texture(_))
+    ));
+}
+
+#[test]
+fn corrupt_dds_headers_are_errors_instead_of_panics() {
+    for (width, height, mip_count) in [
+        (16, 8, 40),
+        (16, 8, u32::MAX),
+        (0, 8, 1),
+        (16, 0, 1),
+        (u32::MAX, u32::MAX, 1),
+        (1 << 20, 8, 1),
+    ] {
+        let mut dds = dds_header(width, height, mip_count, b"DXT1", 0);
+        dds.extend([0u8; 88]);
+        assert!(
+            matches!(Texture::from_dds(&dds), Err(UnbundlerError::Texture(_))),
+            "{}x{} with {} mips",
+            width,
+            height,
+            mip_count
+        );
+        assert!(matches!(
+            Texture::parse(&texture_resource(&dds, &[(u32::MAX, u32::MAX)]), &[]),
+            Err(UnbundlerError::Texture(_))
+        ));
+    }
+
+    //no mips at all is read as one.
+    let mut dds = dds_header(16, 8, 0, b"DXT1", 0);
+    dds.extend([0u8; 64]);
+    assert_eq!(Texture::from_dds(&dds).unwrap().mip_count, 1);
+}
+
+//fields are packed from the lowest bit up, like a bc7 block.
+fn bc7_block(fields: &[(u32, u32)]) -> Vec<u8> {
+    let (mut bits, mut position) = (0u128, 0);
+    for (value, count) in fields {
+        bits |= (*value as u128) << position;
+        position += count;
+    }
+    assert_eq!(position, 128);
+    bits.to_le_bytes().to_vec()
+}
+
+#[test]
+fn bc1_to_bc5_blocks_are_decoded() {
+    //red and blue endpoints, then indices 0, 1, 2 and 3 along the first row.
+    let bc1 = [0x00, 0xf8, 0x1f, 0x00, 0b11100100, 0, 0, 0];
+    let pixels = decode(TextureFormat::Bc1, &bc1, 4, 4).unwrap();
+    assert_eq!(
+        &pixels[0..16],
+        &[255, 0, 0, 255, 0, 0, 255, 255, 170, 0, 85, 255, 85, 0, 170, 255]
+    );
+
+    //with the endpoints swapped the block has three colors and transparent black.
+    let bc1 = [0x1f, 0x00, 0x00, 0xf8, 0b11100100, 0, 0, 0];
+    let pixels = decode(TextureFormat::Bc1, &bc1, 4, 4).unwrap();
+    assert_eq!(&pixels[8..16], &[127, 0, 127, 255, 0, 0, 0, 0]);
+
+    //bc3 alpha from 255 to 0 with indices 0, 1 and 2 on the first three pixels.
+    let mut bc3 = vec![255, 0, 0b10001000, 0, 0, 0, 0, 0];
+    bc3.extend([0x00, 0xf8, 0x00, 0xf8, 0, 0, 0, 0]);
+    let pixels = decode(TextureFormat::Bc3, &bc3, 4, 4).unwrap();
+    assert_eq!(
+        &pixels[0..12],
+        &[255, 0, 0, 255, 255, 0, 0, 0, 255, 0, 0, 218]
+    );
+
+    //bc4 is grey and bc5 puts its second channel in green.
+    let bc4 = [10, 20, 0b111, 0, 0, 0, 0, 0];
+    let pixels = decode(TextureFormat::Bc4, &bc4, 4, 4).unwrap();
+    assert_eq!(&pixels[0..8], &[255, 255, 255, 255, 10, 10, 10, 255]);
+    let bc5 = [10, 20, 0, 0, 0, 0, 0, 0, 30, 40, 1, 0, 0, 0, 0, 0];
+    let pixels = decode(TextureFormat::Bc5, &bc5, 4, 4).unwrap();
+    assert_eq!(&pixels[0..8], &[10, 40, 0, 255, 10, 30, 0, 255]);
+
+    //a 6x2 surface is two blocks wide and the parts over the edge are cropped.
+    let pixels = decode(TextureFormat::Bc4, &[bc4, bc4].concat(), 6, 2).unwrap();
+    assert_eq!(pixels.len(), 6 * 2 * 4);
+    assert!(matches!(
+        decode(TextureFormat::Bc4, &bc4, 6, 2),
+        Err(UnbundlerError::Texture(_))
+    ));
+}
+
+#[test]
+fn bc7_blocks_are_decoded() {
+    //mode 6: one subset of 7 bit endpoints with a p-bit each and 4 bit indices.
+    let mut fields = vec![(1 << 6, 7)];
+    fields.extend([(127, 7), (0, 7)].repeat(4)); //r, g, b and a from 255 to 0.
+    fields.extend([(1, 1), (0, 1)]);
+    fields.push((0, 3)); //the anchor's index has one bit less.
+    fields.push((15, 4));
+    fields.extend([(0, 4)].repeat(14));
+    let pixels = decode(TextureFormat::Bc7, &bc7_block(&fields), 4, 4).unwrap();
+    assert_eq!(&pixels[0..8], &[255, 255, 255, 255, 0, 0, 0, 0]);
+
+    //mode 1, partition 0: the right half is the second subset, whose anchor is the last pixel.
+    let mut fields = vec![(0b10, 2), (0, 6)];
+    fields.extend([(63, 6), (63, 6), (0, 6), (0, 6)]); //red
+    fields.extend([(0, 6), (0, 6), (0, 6), (0, 6)]); //green
+    fields.extend([(0, 6), (0, 6), (63, 6), (0, 6)]); //blue
+    fields.extend([(0, 1), (1, 1)]); //shared p-bits
+    fields.push((0, 2));
+    fields.extend([(0, 3)].repeat(14));
+    fields.push((3, 2));
+    let pixels = decode(TextureFormat::Bc7, &bc7_block(&fields), 4, 4).unwrap();
+    assert_eq!(&pixels[0..4], &[253, 0, 0, 255]);
+    assert_eq!(&pixels[8..12], &[2, 2, 255, 255]);
+    assert_eq!(&pixels[60..64], &[2, 2, 148, 255]);
+
+    //a block without a mode bit is reserved and decoded as transparent black.
+    let pixels = decode(TextureFormat::Bc7, &[0u8; 16], 4, 4).unwrap();
+    assert!(pixels.iter().all(|value| *value == 0));
+}
+
+#[test]
+fn mips_and_cubemap_faces_are_written_as_pngs() {
+    //a 2x2 bgra cubemap with 2 mips. every face is filled with its own number.
+    let mut data = vec![];
+    for face in 0..6u8 {
+        data.extend([face, 100, 200, 255].repeat(4));
+        data.extend([face, 50, 60, 255]);
+    }
+    let texture = Texture {
+        flags: 0,
+        format: TextureFormat::Bgra8,
+        width: 2,
+        height: 2,
+        depth: 1,
+        mip_count: 2,
+        cubemap: true,
+        data,
+    };
+
+    assert_eq!(texture.to_rgba(4, 1).unwrap(), (1, 1, vec![60, 50, 4, 255]));
+    assert!(texture.to_rgba(6, 0).is_err());
+    assert!(texture.to_rgba(0, 2).is_err());
+
+    let png = texture.to_png(3, 0).unwrap();
+    let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (2, 2));
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(
+        &pixels[..info.buffer_size()],
+        &[200, 100, 3, 255].repeat(4)[..]
+    );
+}
+
+//a smooth diagonal gradient with a varying alpha, the kind of image block compression handles well.
+fn gradient(width: u32, height: u32) -> Vec<u8> {
+    let mut pixels = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            let t = (x + y) * 255 / (width + height);
+            pixels.extend([t as u8, 255 - t as u8, 128, 255 - (t / 2) as u8]);
+        }
+    }
+    pixels
+}
+
+fn max_error(left: &[u8], right: &[u8], channels: &[usize]) -> u8 {
+    left.chunks_exact(4)
+        .zip(right.chunks_exact(4))
+        .flat_map(|(left, right)| channels.iter().map(move |c| left[*c].abs_diff(right[*c])))
+        .max()
+        .unwrap()
+}
+
+#[test]
+fn encoded_blocks_decode_close_to_the_original() {
+    let pixels = gradient(10, 6);
+    for (format, channels, tolerance) in [
+        (TextureFormat::Bc1, &[0, 1, 2][..], 24),
+        (TextureFormat::Bc2, &[0, 1, 2, 3][..], 24),
+        (TextureFormat::Bc3, &[0, 1, 2, 3][..], 24),
+        (TextureFormat::Bc4, &[0][..], 8),
+        (TextureFormat::Bc5, &[0, 1][..], 8),
+        (TextureFormat::Bc7, &[0, 1, 2, 3][..], 8),
+        (TextureFormat::Rgba8, &[0, 1, 2, 3][..], 0),
+        (TextureFormat::Bgra8, &[0, 1, 2, 3][..], 0),
+    ] {
+        let data = encode(format, &pixels, 10, 6).unwrap();
+        assert_eq!(
+            Some(data.len()),
+            format.surface_size(10, 6, 1),
+            "{:?}",
+            format
+        );
+        let decoded = decode(format, &data, 10, 6).unwrap();
+        let error = max_error(&pixels, &decoded, channels);
+        assert!(error <= tolerance, "{:?} is off by {}", format, error);
+    }
+
+    //bc1 keeps pixels with less than half alpha transparent.
+    let mut pixels = [200u8, 10, 10, 255].repeat(16);
+    pixels[3] = 0;
+    let decoded = decode(
+        TextureFormat::Bc1,
+        &encode(TextureFormat::Bc1, &pixels, 4, 4).unwrap(),
+        4,
+        4,
+    )
+    .unwrap();
+    assert_eq!(&decoded[0..4], &[0, 0, 0, 0]);
+    assert!(max_error(&pixels[4..], &decoded[4..], &[0, 1, 2, 3]) <= 8);
+
+    assert!(encode(TextureFormat::Bc6h, &pixels, 4, 4).is_err());
+    assert!(encode(TextureFormat::Bc1, &pixels, 4, 3).is_err());
+}
+
+#[test]
+fn mips_are_generated_down_to_one_pixel() {
+    //the left half is black and the right half white, so every mip but the last is too.
+    let mut pixels = vec![];
+    for _y in 0..4 {
+        for x in 0..8 {
+            pixels.extend(if x < 4 {
+                [0, 0, 0, 255]
+            } else {
+                [255, 255, 255, 255]
+            });
+        }
+    }
+
+    let texture = Texture::from_rgba(TextureFormat::Rgba8, 8, 4, pixels, true).unwrap();
+    assert_eq!(texture.mip_count, 4);
+    assert_eq!(texture.data.len(), (32 + 8 + 2 + 1) * 4);
+    assert_eq!(
+        texture.to_rgba(0, 2).unwrap(),
+        (2, 1, vec![0, 0, 0, 255, 255, 255, 255, 255])
+    );
+    assert_eq!(
+        texture.to_rgba(0, 3).unwrap(),
+        (1, 1, vec![128, 128, 128, 255])
+    );
+
+    let texture = Texture::from_rgba(TextureFormat::Bc1, 8, 4, gradient(8, 4), false).unwrap();
+    assert_eq!(texture.mip_count, 1);
+    assert_eq!(texture.data.len(), 16);
+}
+
+#[test]
+fn pngs_of_any_color_type_are_read_as_rgba() {
+    let texture = Texture::from_rgba(TextureFormat::Rgba8, 5, 3, gradient(5, 3), false).unwrap();
+    assert_eq!(
+        Texture::read_png(&texture.to_png(0, 0).unwrap()).unwrap(),
+        (5, 3, gradient(5, 3))
+    );
+
+    let mut png = vec![];
+    let mut encoder = png::Encoder::new(&mut png, 2, 1);
+    encoder.set_color(png::ColorType::Grayscale);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[7, 200]).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        Texture::read_png(&png).unwrap(),
+        (2, 1, vec![7, 7, 7, 255, 200, 200, 200, 255])
+    );
+
+    assert!(matches!(
+        Texture::read_png(b"not a png"),
+        Err(UnbundlerError::Texture(_))
+    ));
+}
+
+#[test]
+fn pngs_and_dds_files_replace_the_texture_beside_them() {
+    //an 8x8 bc3 original with its largest mip streamed.
+    let original = Texture {
+        flags: 0x55,
+        ..Texture::from_rgba(TextureFormat::Bc3, 8, 8, gradient(8, 8), true).unwrap()
+    };
+    let dds = original.to_dds();
+    let mut embedded = dds[..128].to_vec();
+    embedded.extend(&dds[128 + 64..]);
+    let mut resource = vec![];
+    resource.extend(original.flags.to_le_bytes());
+    resource.extend((embedded.len() as u32).to_le_bytes());
+    resource.extend(&embedded);
+    resource.extend([1, 0, 0, 0, 0, 0, 0, 0, 64, 0, 0, 0]);
+    assert_eq!(
+        Texture::parse(&resource, &dds[128..192]).unwrap().data,
+        original.data
+    );
+
+    let dir_path = temp_path("texture_import");
+    let _ = fs::remove_dir_all(&dir_path);
+    fs::create_dir_all(&dir_path).unwrap();
+    fs::write(dir_path.join("0x0000000000000001.texture"), &resource).unwrap();
+    fs::write(
+        dir_path.join("0x0000000000000001.texture.stream"),
+        &dds[128..192],
+    )
+    .unwrap();
+
+    //a 16x4 png replaces it as bc3 with every mip and the original's flags.
+    let replacement =
+        Texture::from_rgba(TextureFormat::Rgba8, 16, 4, gradient(16, 4), false).unwrap();
+    fs::write(
+        dir_path.join("0x0000000000000001.png"),
+        replacement.to_png(0, 0).unwrap(),
+    )
+    .unwrap();
+    //a dds with a texture of its own, as written with --dds.
+    fs::write(dir_path.join("0x0000000000000002.dds"), &dds).unwrap();
+
+    let unbundled_dir = Bundler::read_directory(&dir_path).unwrap();
+    assert_eq!(unbundled_dir.files.len(), 2);
+    let variant = &unbundled_dir.files[0].variants[0];
+    assert!(variant.stream.is_empty());
+    assert_eq!(variant.flags, 0);
+    let imported = Texture::parse(&variant.data, &[]).unwrap();
+    assert_eq!(imported.flags, 0x55);
+    assert_eq!(imported.format, TextureFormat::Bc3);
+    assert_eq!(
+        (imported.width, imported.height, imported.mip_count),
+        (16, 4, 5)
+    );
+    let (_width, _height, pixels) = imported.to_rgba(0, 0).unwrap();
+    assert!(max_error(&gradient(16, 4), &pixels, &[0, 1, 2, 3]) <= 16);
+
+    let file = &unbundled_dir.files[1];
+    assert_eq!((file.path, file.header.extension), (2, TEXTURE));
+    assert_eq!(
+        Texture::parse(&file.variants[0].data, &[]).unwrap().data,
+        original.data
+    );
+
+    //nothing streams any more, so the bundle has no stream sizes and no .stream file.
+    let bundle_path = temp_path("texture_import_bundle");
+    Bundler {
+        file_path: bundle_path.clone(),
+        profile: None,
+    }
+    .bundle(&unbundled_dir)
+    .unwrap();
+    assert!(!Unbundler::stream_path(&bundle_path).exists());
+    let rebuilt = unbundle(&bundle_path);
+    assert!(rebuilt
+        .files
+        .iter()
+        .all(|file| file.variants.iter().all(|variant| variant.flags == 0)));
+    fs::remove_file(bundle_path).unwrap();
+
+    //a png without a texture to take the format from cannot be imported.
+    fs::remove_file(dir_path.join("0x0000000000000001.texture")).unwrap();
+    fs::remove_file(dir_path.join("0x0000000000000001.texture.stream")).unwrap();
+    assert!(matches!(
+        Bundler::read_directory(&dir_path),
+        Err(UnbundlerError::Texture(_))
+    ));
+
+    fs::remove_dir_all(&dir_path).unwrap();
+}
+
+//a 2x2 bgra cubemap with 2 mips. every face is filled with its own color.
+fn cubemap(color: impl Fn(u8) -> [u8; 4]) -> Texture {
+    let data = (0..6u8).flat_map(|face| color(face).repeat(5)).collect();
+    Texture {
+        flags: 9,
+        format: TextureFormat::Bgra8,
+        width: 2,
+        height: 2,
+        depth: 1,
+        mip_count: 2,
+        cubemap: true,
+        data,
+    }
+}
+
+#[test]
+fn cubemap_faces_are_imported_together() {
+    let original = cubemap(|face| [face, 100, 200, 255]);
+    let replacement = cubemap(|face| [face + 20, 30, 40, 255]);
+
+    let dir_path = temp_path("texture_faces");
+    let _ = fs::remove_dir_all(&dir_path);
+    fs::create_dir_all(&dir_path).unwrap();
+    fs::write(dir_path.join("0x3.texture"), original.to_resource()).unwrap();
+    for face in 0..6 {
+        fs::write(
+            dir_path.join(format!("0x3.face{}.png", face)),
+            replacement.to_png(face, 0).unwrap(),
+        )
+        .unwrap();
+    }
+
+    let unbundled_dir = Bundler::read_directory(&dir_path).unwrap();
+    assert_eq!(unbundled_dir.files.len(), 1);
+    let imported = Texture::parse(&unbundled_dir.files[0].variants[0].data, &[]).unwrap();
+    assert_eq!(imported.flags, 9);
+    assert!(imported.cubemap);
+    assert_eq!(imported.data, replacement.data);
+
+    //every face is needed.
+    fs::remove_file(dir_path.join("0x3.face5.png")).unwrap();
+    assert!(matches!(
+        Bundler::read_directory(&dir_path),
+        Err(UnbundlerError::Texture(_))
+    ));
+
+    //a single png, as written without --faces, leaves the cubemap as it is.
+    for face in 0..5 {
+        fs::remove_file(dir_path.join(format!("0x3.face{}.png", face))).unwrap();
+    }
+    fs::write(dir_path.join("0x3.png"), replacement.to_png(0, 0).unwrap()).unwrap();
+    let unbundled_dir = Bundler::read_directory(&dir_path).unwrap();
+    assert_eq!(
+        unbundled_dir.files[0].variants[0].data,
+        original.to_resource()
+    );
+
+    fs::remove_dir_all(&dir_path).unwrap();
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::fs;
+
+use bitsquid_unbundler::bundle_header::BundleHeader;
+use bitsquid_unbundler::extension_registry::TEXTURE;
+use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use bitsquid_unbundler::unbundler::Unbundler;
+use bitsquid_unbundler::verifier::Verifier;
+
+use common::{bundle, sample_directory, sample_file, temp_path};
+
+#[test]
+fn verifier_passes_good_bundles_and_fails_truncated_ones() {
+    let dir_path = temp_path("verify");
+    fs::create_dir_all(&dir_path).unwrap();
+
+    bundle(&dir_path.join("good"), &sample_directory());
+    let good = fs::read(dir_path.join("good")).unwrap();
+    fs::write(dir_path.join("truncated"), &good[..good.len() - 100]).unwrap();
+
+    let reports = Verifier {
+        file_path: dir_path.clone(),
+        profile: None,
+    }
+    .verify()
+    .unwrap();
+
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].bundle_name, "good");
+    assert!(reports[0].passed, "{:?}", reports[0].errors);
+    assert_eq!(reports[0].file_count, sample_directory().files.len());
+    assert_eq!(reports[1].bundle_name, "truncated");
+    assert!(!reports[1].passed);
+
+    fs::remove_dir_all(dir_path).unwrap();
+}
+
+#[test]
+fn stream_files_of_the_wrong_length_fail_the_bundle() {
+    let dir_path = temp_path("verify_stream");
+    fs::create_dir_all(&dir_path).unwrap();
+    let mut files = vec![sample_file(
+        TEXTURE,
+        1,
+        "texture",
+        vec![(0, b"mips".to_vec())],
+    )];
+    files[0].variants[0].stream = b"streamed".to_vec();
+    let bundle_path = dir_path.join("streamed");
+    bundle(
+        &bundle_path,
+        &UnbundledDirectory::new("streamed".to_string(), BundleHeader::default(), files),
+    );
+    fs::write(Unbundler::stream_path(&bundle_path), b"streamed and more").unwrap();
+
+    let reports = Verifier {
+        file_path: dir_path.clone(),
+        profile: None,
+    }
+    .verify()
+    .unwrap();
+
+    assert_eq!(reports.len(), 1);
+    assert!(!reports[0].passed);
+    assert_eq!(reports[0].errors.len(), 1);
+    assert!(reports[0].errors[0].starts_with("The .stream file does not match the bundle"));
+
+    fs::remove_dir_all(dir_path).unwrap();
+}
@@ -0,0 +1,249 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::{error, fmt};
+
+use crate::known_games::{KnownGame, KNOWN_GAMES};
+use crate::vdf::Vdf;
+
+//finds bitsquid games installed through steam by reading steam's library and app manifest files.
+pub struct GameFinder {
+    pub steam_roots: Vec<PathBuf>,
+}
+
+pub struct GameInstall {
+    pub game: &'static KnownGame,
+    pub app_id: String,
+    pub install_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub proton_prefix: Option<PathBuf>, //the wine prefix proton runs the game in, on linux.
+}
+
+impl GameFinder {
+    pub fn new(steam_roots: Vec<PathBuf>) -> GameFinder {
+        GameFinder { steam_roots }
+    }
+
+    //the places steam is installed to by default on this platform.
+    pub fn from_environment() -> GameFinder {
+        let mut steam_roots = vec![];
+
+        if cfg!(windows) {
+            for var in ["ProgramFiles(x86)", "ProgramFiles"] {
+                if let Some(program_files) = env::var_os(var) {
+                    steam_roots.push(PathBuf::from(program_files).join("Steam"));
+                }
+            }
+        } else if let Some(home) = env::var_os("HOME") {
+            let home = PathBuf::from(home);
+            if cfg!(target_os = "macos") {
+                steam_roots.push(home.join("Library/Application Support/Steam"));
+            } else {
+                steam_roots.push(home.join(".steam/steam"));
+                steam_roots.push(home.join(".local/share/Steam"));
+                steam_roots.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+                //the windows client run through wine.
+                steam_roots.push(home.join(".wine/drive_c/Program Files (x86)/Steam"));
+            }
+        }
+
+        GameFinder::new(steam_roots)
+    }
+
+    //every steam library folder, including the one inside each steam install.
+    pub fn libraries(&self) -> Result<Vec<PathBuf>, DiscoveryError> {
+        let mut libraries: Vec<PathBuf> = vec![];
+
+        for steam_root in self.steam_roots.iter() {
+            if !steam_root.join("steamapps").is_dir() {
+                continue;
+            }
+
+            let mut candidates = vec![steam_root.clone()];
+            candidates.append(&mut GameFinder::library_folders(steam_root)?);
+
+            for library in candidates {
+                //~/.steam/steam is usually a link to ~/.local/share/Steam.
+                let library = library.canonicalize().unwrap_or(library);
+                if library.join("steamapps").is_dir() && !libraries.contains(&library) {
+                    libraries.push(library);
+                }
+            }
+        }
+
+        if libraries.is_empty() {
+            return Err(DiscoveryError::NoSteamInstall(self.steam_roots.clone()));
+        }
+
+        Ok(libraries)
+    }
+
+    //every known game which is installed, in the order of KNOWN_GAMES.
+    pub fn installed_games(&self) -> Result<Vec<GameInstall>, DiscoveryError> {
+        let mut installs = vec![];
+
+        for library in self.libraries()? {
+            let steamapps = library.join("steamapps");
+
+            for entry in fs::read_dir(&steamapps)? {
+                let path = entry?.path();
+                let is_manifest = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("appmanifest_") && name.ends_with(".acf"));
+
+                if !is_manifest {
+                    continue;
+                }
+
+                //one broken manifest should not hide every other game.
+                match GameFinder::read_manifest(&steamapps, &path) {
+                    Ok(Some(install)) => installs.push(install),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Skipping app manifest {:?}: {}", path, e),
+                }
+            }
+        }
+
+        installs.sort_by_key(|install| {
+            KNOWN_GAMES
+                .iter()
+                .position(|game| game.id == install.game.id)
+        });
+        Ok(installs)
+    }
+
+    pub fn find(&self, id: &str) -> Result<GameInstall, DiscoveryError> {
+        let game = KnownGame::lookup(id).ok_or(DiscoveryError::UnknownGame(id.to_string()))?;
+
+        self.installed_games()?
+            .into_iter()
+            .find(|install| install.game.id == game.id)
+            .ok_or(DiscoveryError::GameNotFound(game.title.to_string()))
+    }
+
+    //the first known game which is installed.
+    pub fn find_any(&self) -> Result<GameInstall, DiscoveryError> {
+        self.installed_games()?
+            .into_iter()
+            .next()
+            .ok_or(DiscoveryError::GameNotFound(
+                "any known bitsquid game".to_string(),
+            ))
+    }
+
+    fn library_folders(steam_root: &Path) -> Result<Vec<PathBuf>, DiscoveryError> {
+        //newer clients keep the file in steamapps, older ones in config.
+        let vdf_path = [
+            steam_root.join("steamapps").join("libraryfolders.vdf"),
+            steam_root.join("config").join("libraryfolders.vdf"),
+        ]
+        .into_iter()
+        .find(|path| path.is_file());
+
+        let Some(vdf_path) = vdf_path else {
+            return Ok(vec![]);
+        };
+
+        let vdf = Vdf::parse(&fs::read_to_string(&vdf_path)?)?;
+        let Some(folders) = vdf.get("libraryfolders") else {
+            return Ok(vec![]);
+        };
+
+        let mut libraries = vec![];
+        for (key, value) in folders.entries() {
+            //older files map an index straight to the path, newer ones to a section with a path key.
+            let path = match value {
+                Vdf::Value(path) if key.parse::<u32>().is_ok() => Some(path.as_str()),
+                Vdf::Section(_) => value.get_str("path"),
+                _ => None,
+            };
+
+            if let Some(path) = path {
+                libraries.push(PathBuf::from(path));
+            }
+        }
+
+        Ok(libraries)
+    }
+
+    fn read_manifest(
+        steamapps: &Path,
+        manifest_path: &Path,
+    ) -> Result<Option<GameInstall>, DiscoveryError> {
+        let vdf = Vdf::parse(&fs::read_to_string(manifest_path)?)?;
+        let Some(app_state) = vdf.get("AppState") else {
+            return Ok(None);
+        };
+
+        let (Some(app_id), Some(install_dir)) =
+            (app_state.get_str("appid"), app_state.get_str("installdir"))
+        else {
+            return Ok(None);
+        };
+
+        let Some(game) = KnownGame::by_install_dir(install_dir) else {
+            return Ok(None);
+        };
+
+        let install_path = steamapps.join("common").join(install_dir);
+        let Some(data_dir) = game
+            .data_dirs
+            .iter()
+            .map(|data_dir| install_path.join(data_dir))
+            .find(|data_dir| data_dir.is_dir())
+        else {
+            return Ok(None);
+        };
+
+        let proton_prefix = steamapps.join("compatdata").join(app_id).join("pfx");
+
+        Ok(Some(GameInstall {
+            game,
+            app_id: app_id.to_string(),
+            install_path,
+            data_dir,
+            proton_prefix: proton_prefix.is_dir().then_some(proton_prefix),
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub enum DiscoveryError {
+    NoSteamInstall(Vec<PathBuf>),
+    UnknownGame(String),
+    GameNotFound(String),
+    IOError(String),
+    Vdf { line: usize, message: String },
+}
+
+impl fmt::Display for DiscoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoveryError::NoSteamInstall(searched) => {
+                write!(
+                    f,
+                    "No Steam installation was found. Searched: {:?}",
+                    searched
+                )
+            }
+            DiscoveryError::UnknownGame(id) => write!(f, "{} is not a known game.", id),
+            DiscoveryError::GameNotFound(title) => {
+                write!(f, "No Steam library has {} installed.", title)
+            }
+            DiscoveryError::IOError(message) => write!(f, "{}", message),
+            DiscoveryError::Vdf { line, message } => {
+                write!(f, "Invalid vdf file at line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl error::Error for DiscoveryError {}
+
+impl From<io::Error> for DiscoveryError {
+    fn from(value: io::Error) -> Self {
+        DiscoveryError::IOError(format!("{}", value))
+    }
+}
@@ -0,0 +1,55 @@
+//bitsquid and stingray titles and where their bundles are kept, relative to the install directory.
+pub struct KnownGame {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub install_dir: &'static str, //the installdir of the game's appmanifest.
+    pub data_dirs: &'static [&'static str], //the first one which exists is used.
+}
+
+//in order of preference when no game is asked for.
+pub const KNOWN_GAMES: [KnownGame; 5] = [
+    KnownGame {
+        id: "mww",
+        title: "Magicka: Wizard Wars",
+        install_dir: "MagickaWizardWars",
+        data_dirs: &["data_win32_bundled"],
+    },
+    KnownGame {
+        id: "magicka2",
+        title: "Magicka 2",
+        install_dir: "Magicka2",
+        data_dirs: &["data_win32_bundled", "data"],
+    },
+    KnownGame {
+        id: "vermintide",
+        title: "Warhammer: End Times - Vermintide",
+        install_dir: "Warhammer End Times Vermintide",
+        data_dirs: &["bundle"],
+    },
+    KnownGame {
+        id: "vermintide2",
+        title: "Warhammer: Vermintide 2",
+        install_dir: "Warhammer Vermintide 2",
+        data_dirs: &["bundle"],
+    },
+    KnownGame {
+        id: "helldivers",
+        title: "Helldivers",
+        install_dir: "Helldivers",
+        data_dirs: &["data"],
+    },
+];
+
+impl KnownGame {
+    pub fn lookup(id: &str) -> Option<&'static KnownGame> {
+        KNOWN_GAMES
+            .iter()
+            .find(|game| game.id.eq_ignore_ascii_case(id))
+    }
+
+    pub fn by_install_dir(install_dir: &str) -> Option<&'static KnownGame> {
+        KNOWN_GAMES
+            .iter()
+            .find(|game| game.install_dir.eq_ignore_ascii_case(install_dir))
+    }
+}
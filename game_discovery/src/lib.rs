@@ -0,0 +1,3 @@
+pub mod game_finder;
+pub mod known_games;
+pub mod vdf;
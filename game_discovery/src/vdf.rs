@@ -0,0 +1,167 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::game_finder::DiscoveryError;
+
+//valve's text key values format, used by libraryfolders.vdf and the appmanifest_*.acf files.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Vdf {
+    Value(String),
+    Section(Vec<(String, Vdf)>),
+}
+
+enum Token {
+    Text(String),
+    Open,
+    Close,
+}
+
+impl Vdf {
+    //the whole file is returned as a section holding its top level keys.
+    pub fn parse(text: &str) -> Result<Vdf, DiscoveryError> {
+        let mut tokenizer = Tokenizer {
+            chars: text.chars().peekable(),
+            line: 1,
+        };
+        Vdf::parse_section(&mut tokenizer, false)
+    }
+
+    //keys are not case sensitive, e.g. "LibraryFolders" and "libraryfolders" are both in the wild.
+    pub fn get(&self, key: &str) -> Option<&Vdf> {
+        self.entries()
+            .iter()
+            .find(|(name, _value)| name.eq_ignore_ascii_case(key))
+            .map(|(_name, value)| value)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|value| value.as_str())
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Vdf::Value(value) => Some(value),
+            Vdf::Section(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, Vdf)] {
+        match self {
+            Vdf::Value(_) => &[],
+            Vdf::Section(entries) => entries,
+        }
+    }
+
+    fn parse_section(tokenizer: &mut Tokenizer, nested: bool) -> Result<Vdf, DiscoveryError> {
+        let mut entries = vec![];
+
+        loop {
+            let key = match tokenizer.next_token()? {
+                Some(Token::Text(key)) => key,
+                Some(Token::Close) if nested => return Ok(Vdf::Section(entries)),
+                None if !nested => return Ok(Vdf::Section(entries)),
+                Some(Token::Close) => return Err(tokenizer.error("unexpected }")),
+                Some(Token::Open) => return Err(tokenizer.error("expected a key before {")),
+                None => return Err(tokenizer.error("unexpected end of file, expected }")),
+            };
+
+            let value = match tokenizer.next_token()? {
+                Some(Token::Text(value)) => Vdf::Value(value),
+                Some(Token::Open) => Vdf::parse_section(tokenizer, true)?,
+                _ => return Err(tokenizer.error(&format!("expected a value for {}", key))),
+            };
+
+            entries.push((key, value));
+        }
+    }
+}
+
+struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn next_token(&mut self) -> Result<Option<Token>, DiscoveryError> {
+        loop {
+            let Some(c) = self.chars.next() else {
+                return Ok(None);
+            };
+
+            match c {
+                '\n' => self.line += 1,
+                c if c.is_whitespace() => {}
+                '/' if self.chars.peek() == Some(&'/') => self.skip_line(),
+                '{' => return Ok(Some(Token::Open)),
+                '}' => return Ok(Some(Token::Close)),
+                '"' => return self.quoted().map(|text| Some(Token::Text(text))),
+                //conditionals such as [$WIN32] are not needed to find a game.
+                '[' => self.skip_conditional(),
+                c => return Ok(Some(Token::Text(self.unquoted(c)))),
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, DiscoveryError> {
+        let mut text = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(c) => text.push(c),
+                    None => break,
+                },
+                Some(c) => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    text.push(c);
+                }
+                None => break,
+            }
+        }
+
+        Err(self.error("unterminated string"))
+    }
+
+    fn unquoted(&mut self, first: char) -> String {
+        let mut text = first.to_string();
+
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                break;
+            }
+            text.push(c);
+            self.chars.next();
+        }
+
+        text
+    }
+
+    fn skip_line(&mut self) {
+        for c in self.chars.by_ref() {
+            if c == '\n' {
+                self.line += 1;
+                break;
+            }
+        }
+    }
+
+    fn skip_conditional(&mut self) {
+        for c in self.chars.by_ref() {
+            if c == ']' {
+                break;
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> DiscoveryError {
+        DiscoveryError::Vdf {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+}
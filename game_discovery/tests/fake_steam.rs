@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use game_discovery::game_finder::{DiscoveryError, GameFinder};
+use game_discovery::vdf::Vdf;
+
+fn temp_path(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("game_discovery_{}_{}", name, std::process::id()));
+    path
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn manifest(app_id: &str, install_dir: &str) -> String {
+    format!(
+        "\"AppState\"\n{{\n\t\"appid\"\t\t\"{}\"\n\t\"name\"\t\t\"whatever\"\n\t\"installdir\"\t\t\"{}\"\n}}\n",
+        app_id, install_dir
+    )
+}
+
+//a steam install with a second library on another "drive", like a linux install with proton.
+fn fake_steam(root: &Path) -> (PathBuf, PathBuf) {
+    let steam = root.join("Steam");
+    let library = root.join("SteamLibrary");
+
+    write(
+        &steam.join("steamapps").join("libraryfolders.vdf"),
+        &format!(
+            "\"libraryfolders\"\n{{\n\t\"0\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t\t\"apps\"\n\t\t{{\n\t\t\t\"228980\"\t\t\"1\"\n\t\t}}\n\t}}\n\t\"1\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n}}\n",
+            steam.to_str().unwrap().replace('\\', "\\\\"),
+            library.to_str().unwrap().replace('\\', "\\\\"),
+        ),
+    );
+
+    //an unknown game and a known game without any bundles are both ignored.
+    write(
+        &steam.join("steamapps").join("appmanifest_228980.acf"),
+        &manifest("228980", "Steamworks Shared"),
+    );
+    write(
+        &steam.join("steamapps").join("appmanifest_1.acf"),
+        &manifest("1", "Helldivers"),
+    );
+    fs::create_dir_all(steam.join("steamapps").join("common").join("Helldivers")).unwrap();
+
+    write(
+        &library.join("steamapps").join("appmanifest_552500.acf"),
+        &manifest("552500", "Warhammer Vermintide 2"),
+    );
+    fs::create_dir_all(library.join("steamapps/common/Warhammer Vermintide 2/bundle")).unwrap();
+    fs::create_dir_all(library.join("steamapps/compatdata/552500/pfx")).unwrap();
+
+    write(
+        &library.join("steamapps").join("appmanifest_2.acf"),
+        &manifest("2", "MagickaWizardWars"),
+    );
+    fs::create_dir_all(library.join("steamapps/common/MagickaWizardWars/data_win32_bundled"))
+        .unwrap();
+
+    (steam, library)
+}
+
+#[test]
+fn finds_games_across_every_library() {
+    let root = temp_path("libraries");
+    let (steam, library) = fake_steam(&root);
+    let finder = GameFinder::new(vec![steam.clone(), root.join("not_installed")]);
+
+    let libraries = finder.libraries().unwrap();
+    assert_eq!(
+        libraries,
+        vec![
+            steam.canonicalize().unwrap(),
+            library.canonicalize().unwrap()
+        ]
+    );
+
+    let installs = finder.installed_games().unwrap();
+    let ids: Vec<&str> = installs.iter().map(|install| install.game.id).collect();
+    assert_eq!(ids, vec!["mww", "vermintide2"]);
+
+    let mww = finder.find_any().unwrap();
+    assert!(mww
+        .data_dir
+        .ends_with("MagickaWizardWars/data_win32_bundled"));
+    assert!(mww.proton_prefix.is_none());
+
+    let vermintide = finder.find("vermintide2").unwrap();
+    assert_eq!(vermintide.app_id, "552500");
+    assert!(vermintide
+        .data_dir
+        .ends_with("Warhammer Vermintide 2/bundle"));
+    assert!(vermintide
+        .proton_prefix
+        .unwrap()
+        .ends_with("compatdata/552500/pfx"));
+
+    assert!(matches!(
+        finder.find("helldivers"),
+        Err(DiscoveryError::GameNotFound(_))
+    ));
+    assert!(matches!(
+        finder.find("nope"),
+        Err(DiscoveryError::UnknownGame(_))
+    ));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn missing_steam_is_an_error() {
+    let finder = GameFinder::new(vec![temp_path("missing")]);
+    assert!(matches!(
+        finder.find_any(),
+        Err(DiscoveryError::NoSteamInstall(_))
+    ));
+}
+
+#[test]
+fn parses_old_library_folders_and_vdf_syntax() {
+    let vdf = Vdf::parse(
+        "// written by an old client\n\"LibraryFolders\"\n{\n\t\"TimeNextStatsReport\"\t\t\"1234\"\n\t\"1\"\t\t\"D:\\\\SteamLibrary\"\n\tunquoted value [$WIN32]\n}\n",
+    )
+    .unwrap();
+
+    let folders = vdf.get("libraryfolders").unwrap();
+    assert_eq!(folders.get_str("1"), Some("D:\\SteamLibrary"));
+    assert_eq!(folders.get_str("unquoted"), Some("value"));
+    assert_eq!(folders.entries().len(), 3);
+
+    match Vdf::parse("\"a\"\n{\n\t\"b\" \"c\"\n") {
+        Err(DiscoveryError::Vdf { line, .. }) => assert_eq!(line, 4),
+        _ => panic!("an unterminated section should not parse"),
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::murmur::hash64;
+
+const CHUNK_SIZE: u64 = 1 << 16; //candidates a thread hashes before taking more.
+const CHUNKS_PER_SAVE: u64 = 256; //chunks per thread between progress saves.
+
+//a candidate pattern such as "{dirs}/{stems}{suffixes}" or "units/weapons/?d?d".
+//{name} takes every word of the named wordlist, ?d any digit, ?l any lowercase letter,
+//?a any lowercase letter, digit or _, and ?? is a literal ?. anything else is literal.
+pub struct Mask {
+    pub text: String,
+    parts: Vec<Vec<String>>, //the candidates are every combination of one alternative per part.
+    strides: Vec<u64>,       //how many candidates pass before a part's next alternative.
+}
+
+impl Mask {
+    pub fn parse(text: &str, wordlists: &HashMap<String, Vec<String>>) -> Result<Mask, CrackError> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut chars = text.chars();
+
+        while let Some(c) = chars.next() {
+            let part: Vec<String> = match c {
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(CrackError::InvalidMask(text.to_string())),
+                        }
+                    }
+                    match wordlists.get(&name) {
+                        Some(words) => words.clone(),
+                        None => return Err(CrackError::UnknownWordlist(name)),
+                    }
+                }
+                '?' => match chars.next() {
+                    Some('d') => Mask::charset("0123456789"),
+                    Some('l') => Mask::charset("abcdefghijklmnopqrstuvwxyz"),
+                    Some('a') => Mask::charset("abcdefghijklmnopqrstuvwxyz0123456789_"),
+                    Some('?') => {
+                        literal.push('?');
+                        continue;
+                    }
+                    _ => return Err(CrackError::InvalidMask(text.to_string())),
+                },
+                c => {
+                    literal.push(c);
+                    continue;
+                }
+            };
+
+            if !literal.is_empty() {
+                parts.push(vec![std::mem::take(&mut literal)]);
+            }
+            parts.push(part);
+        }
+
+        if !literal.is_empty() {
+            parts.push(vec![literal]);
+        }
+
+        let mut mask = Mask {
+            text: text.to_string(),
+            parts,
+            strides: vec![],
+        };
+        if mask.try_keyspace().is_none() {
+            return Err(CrackError::KeyspaceTooLarge(text.to_string()));
+        }
+
+        //an empty wordlist leaves nothing to pick, and the other parts alone may not fit in a u64.
+        if mask.keyspace() == 0 {
+            return Ok(mask);
+        }
+
+        let mut stride = 1;
+        for part in mask.parts.iter().rev() {
+            mask.strides.push(stride);
+            stride *= part.len() as u64;
+        }
+        mask.strides.reverse();
+        Ok(mask)
+    }
+
+    fn charset(chars: &str) -> Vec<String> {
+        chars.chars().map(String::from).collect()
+    }
+
+    fn try_keyspace(&self) -> Option<u64> {
+        self.parts
+            .iter()
+            .try_fold(1u64, |total, part| total.checked_mul(part.len() as u64))
+    }
+
+    //the number of candidates. a mask with an empty wordlist has none.
+    pub fn keyspace(&self) -> u64 {
+        self.try_keyspace().unwrap()
+    }
+
+    //writes the candidate at an index below the keyspace into the buffer.
+    //the last part changes fastest, so candidates sharing a prefix are hashed together.
+    pub fn candidate(&self, index: u64, buffer: &mut String) {
+        buffer.clear();
+        for (part, stride) in self.parts.iter().zip(self.strides.iter()) {
+            let pick = (index / stride) % part.len() as u64;
+            buffer.push_str(&part[pick as usize]);
+        }
+    }
+}
+
+//one word per line. blank lines and repeated words are skipped.
+pub fn read_wordlist(wordlist_path: &Path) -> io::Result<Vec<String>> {
+    let text = fs::read_to_string(wordlist_path)?;
+    let mut seen = HashSet::new();
+    Ok(text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && seen.insert(*line))
+        .map(String::from)
+        .collect())
+}
+
+//how far each mask has been searched and what was found, so a search can be stopped and resumed.
+//written as text: "mask <keyspace> <position> <mask>" and "hit 0x<hash> <name>" lines.
+#[derive(Default)]
+pub struct CrackProgress {
+    pub positions: BTreeMap<String, (u64, u64)>, //mask to its keyspace and position.
+    pub hits: BTreeMap<u64, String>,
+}
+
+impl CrackProgress {
+    //a missing progress file means nothing has been searched yet.
+    pub fn load(progress_path: &Path) -> Result<CrackProgress, CrackError> {
+        let text = match fs::read_to_string(progress_path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(CrackProgress::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut progress = CrackProgress::default();
+        for line in text.lines().filter(|line| !line.starts_with('#')) {
+            let invalid = || CrackError::InvalidProgress(line.to_string());
+            let mut fields = line.splitn(4, ' ');
+
+            match fields.next() {
+                Some("mask") => {
+                    let keyspace = fields.next().and_then(|n| n.parse().ok());
+                    let position = fields.next().and_then(|n| n.parse().ok());
+                    match (keyspace, position, fields.next()) {
+                        (Some(keyspace), Some(position), Some(mask)) => {
+                            progress
+                                .positions
+                                .insert(mask.to_string(), (keyspace, position));
+                        }
+                        _ => return Err(invalid()),
+                    }
+                }
+                Some("hit") => {
+                    let hash = fields
+                        .next()
+                        .and_then(|hash| {
+                            u64::from_str_radix(hash.trim_start_matches("0x"), 16).ok()
+                        })
+                        .ok_or_else(invalid)?;
+                    let name = fields.collect::<Vec<_>>().join(" ");
+                    progress.hits.insert(hash, name);
+                }
+                Some("") | None => {}
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(progress)
+    }
+
+    pub fn save(&self, progress_path: &Path) -> io::Result<()> {
+        let mut text =
+            String::from("# crack progress. delete this file to search from the start.\n");
+        for (mask, (keyspace, position)) in self.positions.iter() {
+            text.push_str(&format!("mask {} {} {}\n", keyspace, position, mask));
+        }
+        for (hash, name) in self.hits.iter() {
+            text.push_str(&format!("hit {:#018x} {}\n", hash, name));
+        }
+
+        //written beside the old file first, so a stopped search never leaves half a file.
+        let mut temp_path = progress_path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, text)?;
+        fs::rename(&temp_path, progress_path)
+    }
+
+    //where a mask's search continues. the position only carries over if the wordlists are unchanged.
+    fn position(&self, mask: &Mask) -> u64 {
+        match self.positions.get(&mask.text) {
+            Some((keyspace, position)) if *keyspace == mask.keyspace() => *position,
+            _ => 0,
+        }
+    }
+}
+
+//hashes every candidate of every mask on all threads and keeps those which hit a target hash.
+pub struct Cracker {
+    pub targets: HashSet<u64>,
+    pub jobs: usize,
+    pub progress_path: Option<PathBuf>,
+}
+
+impl Cracker {
+    pub fn new(targets: HashSet<u64>) -> Cracker {
+        Cracker {
+            targets,
+            jobs: thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+            progress_path: None,
+        }
+    }
+
+    //on_hit is called from the hashing threads as soon as a hit is found.
+    //returns every hit, including those found before a resume.
+    pub fn crack<F>(&self, masks: &[Mask], on_hit: F) -> Result<BTreeMap<u64, String>, CrackError>
+    where
+        F: Fn(u64, &str) + Sync,
+    {
+        let progress = match &self.progress_path {
+            Some(progress_path) => CrackProgress::load(progress_path)?,
+            None => CrackProgress::default(),
+        };
+        let progress = Mutex::new(progress);
+
+        let jobs = self.jobs.max(1) as u64;
+        for mask in masks.iter() {
+            let keyspace = mask.keyspace();
+            let mut position = progress.lock().unwrap().position(mask);
+
+            while position < keyspace {
+                let end =
+                    keyspace.min(position.saturating_add(jobs * CHUNK_SIZE * CHUNKS_PER_SAVE));
+                let next = AtomicU64::new(position);
+
+                thread::scope(|scope| {
+                    for _ in 0..jobs {
+                        scope.spawn(|| {
+                            let mut candidate = String::new();
+                            loop {
+                                let start = next.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+                                if start >= end {
+                                    break;
+                                }
+
+                                for index in start..end.min(start + CHUNK_SIZE) {
+                                    mask.candidate(index, &mut candidate);
+                                    let hash = hash64(&candidate);
+                                    if self.targets.contains(&hash) {
+                                        on_hit(hash, &candidate);
+                                        progress
+                                            .lock()
+                                            .unwrap()
+                                            .hits
+                                            .insert(hash, candidate.clone());
+                                    }
+                                }
+                            }
+                        });
+                    }
+                });
+
+                position = end;
+                let mut progress = progress.lock().unwrap();
+                progress
+                    .positions
+                    .insert(mask.text.clone(), (keyspace, position));
+                if let Some(progress_path) = &self.progress_path {
+                    progress.save(progress_path)?;
+                }
+            }
+        }
+
+        Ok(progress.into_inner().unwrap().hits)
+    }
+}
+
+#[derive(Debug)]
+pub enum CrackError {
+    IOError(io::Error),
+    UnknownWordlist(String),
+    InvalidMask(String),
+    KeyspaceTooLarge(String),
+    InvalidProgress(String),
+}
+
+impl From<io::Error> for CrackError {
+    fn from(e: io::Error) -> Self {
+        CrackError::IOError(e)
+    }
+}
+
+impl fmt::Display for CrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrackError::IOError(e) => write!(f, "IO error: {}", e),
+            CrackError::UnknownWordlist(name) => write!(f, "No wordlist is named {}", name),
+            CrackError::InvalidMask(mask) => write!(f, "Invalid mask: {}", mask),
+            CrackError::KeyspaceTooLarge(mask) => {
+                write!(f, "The mask {} has more than 2^64 candidates", mask)
+            }
+            CrackError::InvalidProgress(line) => write!(f, "Invalid progress line: {}", line),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use crate::lookup_table::LookupTable;
+use crate::murmur::hash64;
+
+//maps hashes back to the strings they were made from.
+#[derive(Default)]
+pub struct Dictionary {
+    names: HashMap<u64, String>,
+    ids: HashMap<u32, String>,
+    tables: Vec<LookupTable>, //searched after the names, without being loaded into them.
+    collisions: Vec<Collision>,
+}
+
+//two different strings with the same hash. the first string is the one kept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collision {
+    pub hash: u64,
+    pub kept: String,
+    pub other: String,
+}
+
+impl Dictionary {
+    //one candidate string per line. blank lines are skipped.
+    pub fn from_wordlist(wordlist_path: &Path) -> io::Result<Dictionary> {
+        let mut dictionary = Dictionary::default();
+        dictionary.add_wordlist(wordlist_path)?;
+        Ok(dictionary)
+    }
+
+    pub fn add_wordlist(&mut self, wordlist_path: &Path) -> io::Result<usize> {
+        let text = fs::read_to_string(wordlist_path)?;
+        Ok(self.add_all(text.lines()))
+    }
+
+    //a names index is a wordlist with the hash in front of every name, one "0x<hash> <name>" per line.
+    //it is kept across runs and grows as names are found. a missing index is empty.
+    pub fn load_index(index_path: &Path) -> io::Result<Dictionary> {
+        let mut dictionary = Dictionary::default();
+        match dictionary.add_index(index_path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(dictionary),
+        }
+    }
+
+    //the hashes are only there for people reading the file. every name is hashed again.
+    pub fn add_index(&mut self, index_path: &Path) -> io::Result<usize> {
+        let text = fs::read_to_string(index_path)?;
+        let names = text
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once(' '))
+            .map(|(_hash, name)| name);
+        Ok(self.add_all(names))
+    }
+
+    //sorted by hash so that the index diffs well between runs.
+    pub fn save_index(&self, index_path: &Path) -> io::Result<()> {
+        let mut names: Vec<(u64, &str)> = self.names().collect();
+        names.sort();
+
+        let mut text = String::new();
+        for (hash, name) in names {
+            text.push_str(&format!("{:#018x} {}\n", hash, name));
+        }
+        fs::write(index_path, text)
+    }
+
+    //returns how many of the strings were not in the dictionary yet.
+    pub fn add_all<'a>(&mut self, words: impl IntoIterator<Item = &'a str>) -> usize {
+        words.into_iter().filter(|word| self.add(word)).count()
+    }
+
+    //returns false if the string is empty or was already added.
+    //a different string with the hash of a known one is recorded as a collision.
+    pub fn add(&mut self, word: &str) -> bool {
+        let word = word.trim();
+        if word.is_empty() {
+            return false;
+        }
+
+        self.insert(hash64(word), word)
+    }
+
+    fn insert(&mut self, hash: u64, word: &str) -> bool {
+        if let Some(kept) = self.lookup(hash) {
+            if kept != word {
+                self.collisions.push(Collision {
+                    hash,
+                    kept: kept.to_string(),
+                    other: word.to_string(),
+                });
+            }
+            return false;
+        }
+
+        self.ids
+            .entry((hash >> 32) as u32)
+            .or_insert_with(|| word.to_string());
+        self.names.insert(hash, word.to_string());
+        true
+    }
+
+    //tables are kept as they are and searched when a hash is not in the dictionary itself.
+    pub fn add_table(&mut self, table: LookupTable) {
+        self.tables.push(table);
+    }
+
+    pub fn lookup(&self, hash: u64) -> Option<&str> {
+        match self.names.get(&hash) {
+            Some(name) => Some(name.as_str()),
+            None => self.tables.iter().find_map(|table| table.lookup(hash)),
+        }
+    }
+
+    //for the 32 bit ids used inside resources.
+    pub fn lookup32(&self, id: u32) -> Option<&str> {
+        match self.ids.get(&id) {
+            Some(name) => Some(name.as_str()),
+            None => self.tables.iter().find_map(|table| table.lookup32(id)),
+        }
+    }
+
+    pub fn collisions(&self) -> &[Collision] {
+        &self.collisions
+    }
+
+    //the names added to the dictionary, not those in its tables.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.names.iter().map(|(hash, name)| (*hash, name.as_str()))
+    }
+
+    //merges the names and every table into one table. where tables disagree the
+    //dictionary's own names win, then the earlier tables. check collisions() afterwards.
+    pub fn to_table(&mut self) -> io::Result<LookupTable> {
+        let tables = std::mem::take(&mut self.tables);
+        for table in tables.iter() {
+            for (hash, name) in table.names() {
+                self.insert(hash, name);
+            }
+        }
+
+        LookupTable::build(self.names())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod cracker;
+pub mod dictionary;
+pub mod lookup_table;
+pub mod murmur;
@@ -0,0 +1,188 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"MMHT";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 24; //magic, version, entry count u64, pool size u64.
+const ENTRY_SIZE: usize = 16; //hash u64, pool offset u32, length u32.
+
+//a compact table of hashes and the names they were made from.
+//entries are fixed size and sorted by hash, followed by a pool of the names' bytes,
+//so the table is searched in place and can be memory mapped as it is.
+//all numbers are little endian and every entry is 8 byte aligned.
+pub struct LookupTable {
+    bytes: Vec<u8>,
+    count: usize,
+}
+
+impl LookupTable {
+    pub fn open(table_path: &Path) -> io::Result<LookupTable> {
+        LookupTable::from_bytes(fs::read(table_path)?)
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> io::Result<LookupTable> {
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_string());
+
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != MAGIC {
+            return Err(invalid("not a lookup table"));
+        }
+        if LookupTable::u32_at(&bytes, 4) != VERSION {
+            return Err(invalid("unsupported lookup table version"));
+        }
+
+        let count = LookupTable::u64_at(&bytes, 8) as usize;
+        let pool_size = LookupTable::u64_at(&bytes, 16) as usize;
+        let size = count
+            .checked_mul(ENTRY_SIZE)
+            .and_then(|entries| entries.checked_add(pool_size))
+            .and_then(|size| size.checked_add(HEADER_SIZE));
+        if size != Some(bytes.len()) {
+            return Err(invalid("the lookup table is truncated"));
+        }
+
+        let table = LookupTable { bytes, count };
+        let mut previous = None;
+        for index in 0..count {
+            let (hash, offset, len) = table.entry(index);
+            if previous.is_some_and(|previous| previous >= hash) {
+                return Err(invalid("the lookup table is not sorted"));
+            }
+            if offset + len > pool_size {
+                return Err(invalid("a lookup table name is outside the string pool"));
+            }
+            previous = Some(hash);
+        }
+
+        Ok(table)
+    }
+
+    //names are written in hash order and only the first name of a hash is kept.
+    //names are addressed by u32 offsets, so a pool beyond 4GiB is an error.
+    pub fn build<'a>(names: impl IntoIterator<Item = (u64, &'a str)>) -> io::Result<LookupTable> {
+        let mut names: Vec<(u64, &str)> = names.into_iter().collect();
+        names.sort_by_key(|(hash, _name)| *hash);
+        names.dedup_by_key(|(hash, _name)| *hash);
+
+        //the entries are checked before anything is allocated for the pool.
+        let too_large = || {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "the names do not fit in the 4GiB string pool of a lookup table",
+            )
+        };
+        let mut entries = Vec::with_capacity(names.len() * ENTRY_SIZE);
+        let mut offset = 0;
+        for (hash, name) in names.iter() {
+            entries.extend(hash.to_le_bytes());
+            entries.extend(
+                u32::try_from(offset)
+                    .map_err(|_| too_large())?
+                    .to_le_bytes(),
+            );
+            entries.extend(
+                u32::try_from(name.len())
+                    .map_err(|_| too_large())?
+                    .to_le_bytes(),
+            );
+            offset += name.len();
+        }
+
+        let pool_size = offset;
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + entries.len() + pool_size);
+        bytes.extend(MAGIC);
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend((names.len() as u64).to_le_bytes());
+        bytes.extend((pool_size as u64).to_le_bytes());
+        bytes.extend(entries);
+        for (_hash, name) in names.iter() {
+            bytes.extend(name.as_bytes());
+        }
+
+        Ok(LookupTable {
+            bytes,
+            count: names.len(),
+        })
+    }
+
+    pub fn save(&self, table_path: &Path) -> io::Result<()> {
+        fs::write(table_path, &self.bytes)
+    }
+
+    pub fn lookup(&self, hash: u64) -> Option<&str> {
+        let index = self.first_at_least(hash);
+        if index < self.count && self.entry(index).0 == hash {
+            self.name(index)
+        } else {
+            None
+        }
+    }
+
+    //ids are the upper half of the hash, so the first entry in their range is the one.
+    pub fn lookup32(&self, id: u32) -> Option<&str> {
+        let index = self.first_at_least((id as u64) << 32);
+        if index < self.count && (self.entry(index).0 >> 32) as u32 == id {
+            self.name(index)
+        } else {
+            None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    //in hash order. names which are not valid utf8 are skipped.
+    pub fn names(&self) -> impl Iterator<Item = (u64, &str)> {
+        (0..self.count).filter_map(|index| self.name(index).map(|name| (self.entry(index).0, name)))
+    }
+
+    //the same "0x<hash> <name>" lines as a names index.
+    pub fn export_text(&self) -> String {
+        let mut text = String::new();
+        for (hash, name) in self.names() {
+            text.push_str(&format!("{:#018x} {}\n", hash, name));
+        }
+        text
+    }
+
+    fn first_at_least(&self, hash: u64) -> usize {
+        let (mut low, mut high) = (0, self.count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.entry(middle).0 < hash {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    fn entry(&self, index: usize) -> (u64, usize, usize) {
+        let start = HEADER_SIZE + index * ENTRY_SIZE;
+        (
+            LookupTable::u64_at(&self.bytes, start),
+            LookupTable::u32_at(&self.bytes, start + 8) as usize,
+            LookupTable::u32_at(&self.bytes, start + 12) as usize,
+        )
+    }
+
+    fn name(&self, index: usize) -> Option<&str> {
+        let (_hash, offset, len) = self.entry(index);
+        let start = HEADER_SIZE + self.count * ENTRY_SIZE + offset;
+        std::str::from_utf8(&self.bytes[start..start + len]).ok()
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+    }
+}
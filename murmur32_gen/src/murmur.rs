@@ -0,0 +1,41 @@
+const M: u64 = 0xc6a4a7935bd1e995;
+const R: u32 = 47;
+
+//MurmurHash64A, which the engine hashes resource names and types with.
+pub fn murmur64a(bytes: &[u8], seed: u64) -> u64 {
+    let mut hash = seed ^ (bytes.len() as u64).wrapping_mul(M);
+
+    let mut chunks = bytes.chunks_exact(8);
+    for chunk in chunks.by_ref() {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        hash ^= k;
+        hash = hash.wrapping_mul(M);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            hash ^= (*byte as u64) << (8 * i);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> R;
+    hash = hash.wrapping_mul(M);
+    hash ^= hash >> R;
+    hash
+}
+
+//the 64 bit hash of a resource name or extension.
+pub fn hash64(name: &str) -> u64 {
+    murmur64a(name.as_bytes(), 0)
+}
+
+//ids inside resources, such as the keys of strings files, are the upper half of the 64 bit hash.
+pub fn hash32(name: &str) -> u32 {
+    (hash64(name) >> 32) as u32
+}
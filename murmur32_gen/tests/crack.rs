@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::Mutex;
+
+use murmur32_gen::cracker::{CrackError, CrackProgress, Cracker, Mask};
+use murmur32_gen::murmur::hash64;
+
+fn wordlists() -> HashMap<String, Vec<String>> {
+    let mut wordlists = HashMap::new();
+    wordlists.insert(
+        "dirs".to_string(),
+        vec!["units/weapons".to_string(), "units/beings".to_string()],
+    );
+    wordlists.insert(
+        "stems".to_string(),
+        vec!["sword".to_string(), "player".to_string()],
+    );
+    wordlists.insert(
+        "suffixes".to_string(),
+        vec!["_01".to_string(), "_lod0".to_string()],
+    );
+    wordlists
+}
+
+#[test]
+fn masks_enumerate_every_combination() {
+    let mask = Mask::parse("{dirs}/{stems}{suffixes}", &wordlists()).unwrap();
+    assert_eq!(mask.keyspace(), 8);
+
+    let mut candidate = String::new();
+    let mut candidates = vec![];
+    for index in 0..mask.keyspace() {
+        mask.candidate(index, &mut candidate);
+        candidates.push(candidate.clone());
+    }
+    assert_eq!(candidates[0], "units/weapons/sword_01");
+    assert_eq!(candidates[1], "units/weapons/sword_lod0");
+    assert_eq!(candidates[7], "units/beings/player_lod0");
+    assert_eq!(candidates.iter().collect::<HashSet<_>>().len(), 8);
+
+    let mask = Mask::parse("level_?d?d??", &wordlists()).unwrap();
+    assert_eq!(mask.keyspace(), 100);
+    mask.candidate(42, &mut candidate);
+    assert_eq!(candidate, "level_42?");
+
+    assert!(matches!(
+        Mask::parse("{missing}", &wordlists()),
+        Err(CrackError::UnknownWordlist(_))
+    ));
+    assert!(matches!(
+        Mask::parse("?x", &wordlists()),
+        Err(CrackError::InvalidMask(_))
+    ));
+    assert!(matches!(
+        Mask::parse("{dirs}/{stems", &wordlists()),
+        Err(CrackError::InvalidMask(_))
+    ));
+    assert!(matches!(
+        Mask::parse("?a?a?a?a?a?a?a?a?a?a?a?a?a", &wordlists()),
+        Err(CrackError::KeyspaceTooLarge(_))
+    ));
+
+    //an empty wordlist makes any mask empty, however large the rest of it is.
+    let mut with_empty = wordlists();
+    with_empty.insert("none".to_string(), vec![]);
+    let mask = Mask::parse("{none}?a?a?a?a?a?a?a?a?a?a?a?a?a", &with_empty).unwrap();
+    assert_eq!(mask.keyspace(), 0);
+}
+
+#[test]
+fn cracker_finds_targets_and_resumes_from_its_progress() {
+    let mut progress_path = std::env::temp_dir();
+    progress_path.push(format!("murmur32_gen_crack_{}", std::process::id()));
+
+    let masks = vec![
+        Mask::parse("{dirs}/{stems}", &wordlists()).unwrap(),
+        Mask::parse("{dirs}/{stems}_?d?d?d", &wordlists()).unwrap(),
+    ];
+    let targets: HashSet<u64> = ["units/beings/player", "units/weapons/sword_042", "nope"]
+        .iter()
+        .map(|name| hash64(name))
+        .collect();
+
+    let mut cracker = Cracker::new(targets);
+    cracker.jobs = 3;
+    cracker.progress_path = Some(progress_path.clone());
+
+    let reported = Mutex::new(vec![]);
+    let hits = cracker
+        .crack(&masks, |hash, name| {
+            reported.lock().unwrap().push((hash, name.to_string()))
+        })
+        .unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(
+        hits.get(&hash64("units/weapons/sword_042"))
+            .map(|name| name.as_str()),
+        Some("units/weapons/sword_042")
+    );
+    assert_eq!(reported.into_inner().unwrap().len(), 2);
+
+    let progress = CrackProgress::load(&progress_path).unwrap();
+    assert_eq!(progress.positions["{dirs}/{stems}_?d?d?d"], (4000, 4000));
+    assert_eq!(progress.hits, hits);
+
+    //a finished search resumes at its end, but still returns what it found.
+    let resumed = cracker
+        .crack(&masks, |_hash, _name| panic!("nothing is searched again"))
+        .unwrap();
+    assert_eq!(resumed, hits);
+
+    fs::remove_file(progress_path).unwrap();
+}
@@ -0,0 +1,78 @@
+use std::fs;
+
+use murmur32_gen::dictionary::Dictionary;
+use murmur32_gen::murmur::{hash32, hash64, murmur64a};
+
+//the extension table is made of engine hashes, so every entry is a test vector.
+#[test]
+fn hash64_matches_the_extension_table() {
+    let table = include_str!("../../bitsquid_unbundler/src/extensions.txt");
+
+    for line in table.lines().filter(|line| !line.starts_with('#')) {
+        let (hash, extension) = line.split_once(' ').unwrap();
+        let hash = u64::from_str_radix(hash.trim_start_matches("0x"), 16).unwrap();
+        assert_eq!(hash64(extension), hash, "{}", extension);
+    }
+}
+
+#[test]
+fn hashes_engine_names_of_every_tail_length() {
+    assert_eq!(hash64("core/shaders"), 0x3ff40dbdd8dc16e8);
+    assert_eq!(hash32("core/shaders"), 0x3ff40dbd);
+    assert_eq!(hash64(""), 0);
+    assert_eq!(hash64("a"), 0x071717d2d36b6b11);
+    assert_eq!(hash64("abcdefgh"), 0xafdb0257ff41aa98);
+    assert_eq!(hash64("abcdefghi"), 0xc9b9d84356146ac2);
+    assert_ne!(murmur64a(b"lua", 1), hash64("lua"));
+}
+
+#[test]
+fn dictionary_maps_hashes_back_to_names() {
+    let mut wordlist_path = std::env::temp_dir();
+    wordlist_path.push(format!("murmur32_gen_wordlist_{}", std::process::id()));
+    fs::write(&wordlist_path, "core/shaders\n\n  lua  \ncore/shaders\n").unwrap();
+
+    let mut dictionary = Dictionary::from_wordlist(&wordlist_path).unwrap();
+    assert_eq!(dictionary.len(), 2);
+    assert_eq!(dictionary.lookup(0x3ff40dbdd8dc16e8), Some("core/shaders"));
+    assert_eq!(dictionary.lookup(0xa14e8dfa2cd117e2), Some("lua"));
+    assert_eq!(dictionary.lookup32(0x3ff40dbd), Some("core/shaders"));
+    assert_eq!(dictionary.lookup(0x1234), None);
+
+    assert_eq!(dictionary.add_all(["lua", "texture"]), 1);
+    assert_eq!(dictionary.lookup(0xcd4238c6a0c69e32), Some("texture"));
+
+    fs::remove_file(wordlist_path).unwrap();
+}
+
+#[test]
+fn names_index_grows_across_runs() {
+    let mut index_path = std::env::temp_dir();
+    index_path.push(format!("murmur32_gen_names_{}", std::process::id()));
+
+    //the first run starts from nothing.
+    let mut dictionary = Dictionary::load_index(&index_path).unwrap();
+    assert!(dictionary.is_empty());
+    dictionary.add_all(["scripts/game/player", "core/shaders"]);
+    dictionary.save_index(&index_path).unwrap();
+
+    let text = fs::read_to_string(&index_path).unwrap();
+    assert_eq!(
+        text.lines().next(),
+        Some("0x3ff40dbdd8dc16e8 core/shaders"),
+        "the index is sorted by hash"
+    );
+
+    let mut dictionary = Dictionary::load_index(&index_path).unwrap();
+    assert_eq!(dictionary.len(), 2);
+    assert_eq!(
+        dictionary.add_all(["core/shaders", "units/beings/player"]),
+        1
+    );
+    assert_eq!(
+        dictionary.lookup(hash64("scripts/game/player")),
+        Some("scripts/game/player")
+    );
+
+    fs::remove_file(index_path).unwrap();
+}
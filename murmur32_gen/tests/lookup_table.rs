@@ -0,0 +1,83 @@
+use murmur32_gen::dictionary::{Collision, Dictionary};
+use murmur32_gen::lookup_table::LookupTable;
+use murmur32_gen::murmur::{hash32, hash64};
+
+fn names() -> Vec<&'static str> {
+    vec![
+        "core/shaders",
+        "scripts/game/player",
+        "units/beings/hero",
+        "settings",
+    ]
+}
+
+#[test]
+fn table_is_searched_in_place_after_a_round_trip() {
+    let mut table_path = std::env::temp_dir();
+    table_path.push(format!("murmur32_gen_table_{}", std::process::id()));
+
+    let table = LookupTable::build(names().into_iter().map(|name| (hash64(name), name))).unwrap();
+    table.save(&table_path).unwrap();
+    let table = LookupTable::open(&table_path).unwrap();
+
+    assert_eq!(table.len(), 4);
+    for name in names() {
+        assert_eq!(table.lookup(hash64(name)), Some(name));
+        assert_eq!(table.lookup32(hash32(name)), Some(name));
+    }
+    assert_eq!(table.lookup(0), None);
+    assert_eq!(table.lookup(u64::MAX), None);
+    assert_eq!(table.lookup32(0x3ff40dbe), None);
+
+    let text = table.export_text();
+    assert_eq!(text.lines().count(), 4);
+    assert!(text.contains("0x3ff40dbdd8dc16e8 core/shaders\n"));
+
+    let bytes = std::fs::read(&table_path).unwrap();
+    assert!(LookupTable::from_bytes(bytes[..bytes.len() - 1].to_vec()).is_err());
+    assert!(LookupTable::from_bytes(b"not a table at all, really".to_vec()).is_err());
+
+    std::fs::remove_file(table_path).unwrap();
+}
+
+#[test]
+fn merging_tables_reports_collisions() {
+    //a teammate's table which names the hash of "settings" differently.
+    let theirs = LookupTable::build([
+        (hash64("settings"), "not/settings"),
+        (hash64("units/beings/hero"), "units/beings/hero"),
+    ])
+    .unwrap();
+
+    let mut dictionary = Dictionary::default();
+    dictionary.add_all(["core/shaders", "settings"]);
+    dictionary.add_table(theirs);
+    assert_eq!(
+        dictionary.lookup(hash64("units/beings/hero")),
+        Some("units/beings/hero")
+    );
+    assert_eq!(
+        dictionary.lookup32(hash32("units/beings/hero")),
+        Some("units/beings/hero")
+    );
+
+    let merged = dictionary.to_table().unwrap();
+    assert_eq!(merged.len(), 3);
+    assert_eq!(merged.lookup(hash64("settings")), Some("settings"));
+    assert_eq!(
+        dictionary.collisions(),
+        &[Collision {
+            hash: hash64("settings"),
+            kept: "settings".to_string(),
+            other: "not/settings".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn names_beyond_a_4gib_pool_are_an_error() {
+    //the same 1MiB name under 4097 hashes, which is checked before the pool is allocated.
+    let name = "a".repeat(1 << 20);
+    let names = (0..4097u64).map(|hash| (hash, name.as_str()));
+    assert!(LookupTable::build(names).is_err());
+}
@@ -1,11 +1,23 @@
-use std::{env, path::PathBuf};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::{env, path::PathBuf, process};
 
+use bitsquid_unbundler::bundler::Bundler;
+use bitsquid_unbundler::file_filter::FileFilter;
+use bitsquid_unbundler::game_profile::GameProfile;
+use bitsquid_unbundler::strings::StringsFormat;
 use bitsquid_unbundler::unbundler::Unbundler;
+use bitsquid_unbundler::verifier::Verifier;
 use clap::{arg, command, value_parser, ArgMatches};
 use compiler_bootstrap::bootstrap::Bootstrapper;
-use registry::{Hive, Security};
+use game_discovery::game_finder::GameFinder;
+use game_discovery::known_games::KnownGame;
+use murmur32_gen::cracker::{self, Mask};
+use murmur32_gen::dictionary::Dictionary;
+use murmur32_gen::lookup_table::LookupTable;
 
-use crate::file_writer::FileWriter;
+use crate::file_writer::{FileWriter, TextureMode};
+use crate::report_printer::{OutputFormat, ReportPrinter};
 
 pub struct CommandLine {
     pub matches: ArgMatches,
@@ -19,7 +31,7 @@ impl CommandLine {
             .author("Alias")
             .about("A toolchain for developers reverse engineering the bitsquid engine.")
 
-            .arg(arg!(-t --tool <TOOL> "Currently supported tools: -t bitsquid_unbundler\n-t luajit_decompiler\ncompiler_bootstrap\n")
+            .arg(arg!(-t --tool <TOOL> "Currently supported tools: -t bitsquid_unbundler\n-t bitsquid_bundler\n-t verify\n-t list\n-t where\n-t diff\n-t harvest\n-t crack\n-t table\n-t packages\n-t png\n-t luajit_decompiler\ncompiler_bootstrap\n")
                 .required(true).value_parser(value_parser!(String)))
 
             .arg(arg!(-i --input <INPUT> "Input may be a path to a file or directory.")
@@ -34,73 +46,347 @@ impl CommandLine {
             .arg(arg!(-r --data_dir <DATA_DIR> "The data directory which contains the bundles.")
                 .required(false).value_parser(value_parser!(String)))
 
+            .arg(arg!(--steam <STEAM_DIR> "The Steam directory to look for installed games in when no -i is given.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(-g --game <GAME> "A built in game profile (mww, magicka2, vermintide, vermintide2, helldivers, wotr) or a path to a .json or .toml profile. Detected from each bundle's header version when not given.")
+                .required(false).value_parser(value_parser!(String)))
+
             .arg(arg!(-d --dds ... "Unbundles texture files as dds files instead.")
                 .required(false))
 
+            .arg(arg!(--png ... "Unbundles texture files as png images instead. The png tool always writes pngs.")
+                .required(false))
+
+            .arg(arg!(--mip <MIP> "The mip level written as a png. Defaults to 0, the largest.")
+                .required(false).value_parser(value_parser!(u32)))
+
+            .arg(arg!(--faces ... "Writes every face of a cubemap as its own png instead of only the first.")
+                .required(false))
+
+            .arg(arg!(--strings <FORMAT> "Also writes each strings resource as a csv or json table beside it. The bundler reads an edited table back in.")
+                .required(false).value_parser(["csv", "json"]))
+
+            .arg(arg!(-e --extensions <FILE> "A file of extension hashes and names, one \"0x<hash> <name>\" per line, to use on top of the built in table.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--unknown_extensions <FILE> "Writes the extension hashes which have no name to this file once the tool is done.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--wordlist <FILE> "A file of candidate resource names, one per line, used to restore the original file and directory names.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--names <FILE> "A names index kept across runs. Names from --wordlist are added to it and it is used to restore file and directory names.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--table <TABLES> "Comma separated lookup tables written by the table tool, used to restore file and directory names.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--words <WORDLISTS> "Named wordlists for the crack tool, such as dirs=dirs.txt,stems=stems.txt,suffixes=suffixes.txt.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--mask <MASKS> "Comma separated crack masks, such as \"{dirs}/{stems}{suffixes},units/weapons/?d?d\". Defaults to {dirs}/{stems} and {dirs}/{stems}{suffixes}.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--progress <FILE> "The crack tool saves its progress and hits to this file and resumes from it.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(-j --jobs <JOBS> "The number of bundles to unbundle at the same time. Defaults to 1.")
+                .required(false).value_parser(value_parser!(usize)))
+
+            .arg(arg!(-f --format <FORMAT> "The format of printed reports: text, json or csv. Defaults to text.")
+                .required(false).value_parser(["text", "json", "csv"]))
+
+            .arg(arg!(--new <NEW> "The newer bundle file or directory which the diff tool compares the input against.\nLua resources are compared byte for byte like any other. Diffing their disassembly is not supported, since the luajit decompiler does not build in the workspace.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(-x --index <INDEX> "The reverse index file written and read by the where tool.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--type <TYPES> "Only unbundle or list files of these comma separated types, such as lua,strings.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--hash <HASHES> "Only unbundle or list files with these comma separated name hashes, such as 0x1a2b3c4d5e6f7081.")
+                .required(false).value_parser(value_parser!(String)))
+
+            .arg(arg!(--name <GLOB> "Only unbundle or list files whose name matches this glob, such as \"scripts/**/*.lua\". Files without a known name are matched by their hex name.")
+                .required(false).value_parser(value_parser!(String)))
+
             .get_matches();
         CommandLine { matches }
     }
 
-    //TODO: move this somewhere else. like a file for finding env vars.
-    fn find_mww_bundles() -> String {
-        let steam_dir = Hive::LocalMachine
-            .open(r"SOFTWARE\WOW6432Node\Valve\Steam", Security::Read)
-            .expect("Failed to read winreg for steam.\n");
-        let data_win32_path = r"\steamapps\common\MagickaWizardWars\data_win32_bundled";
-        //TODO: don't unwrap. there's an edge case where they don't have mww installed through steam or otherwise.
-        let bundle_directory = String::from(format!(
-            "{}{}",
-            steam_dir.value("InstallPath").unwrap().to_string(),
-            data_win32_path
-        ));
-        bundle_directory
+    //the bundle file or directory to read. defaults to the data directory of an installed game.
+    fn input_path(&self) -> String {
+        if let Some(path) = self.matches.get_one::<String>("input") {
+            return String::from(path);
+        }
+
+        let finder = match self.matches.get_one::<String>("steam") {
+            Some(steam_dir) => GameFinder::new(vec![PathBuf::from(steam_dir)]),
+            None => GameFinder::from_environment(),
+        };
+
+        //a game profile also picks which game's bundles to read, so it must be one that can be found.
+        let install = match self.matches.get_one::<String>("game") {
+            Some(game) if KnownGame::lookup(game).is_none() => {
+                eprintln!("{} is not a game which can be found. Pass the bundle file or directory with -i.", game);
+                process::exit(1);
+            }
+            Some(game) => finder.find(game),
+            None => finder.find_any(),
+        };
+
+        match install {
+            Ok(install) => {
+                eprintln!(
+                    "Using the {} bundles in {:?}",
+                    install.game.title, install.data_dir
+                );
+                install.data_dir.to_string_lossy().into_owned()
+            }
+            Err(e) => {
+                eprintln!("{}\nPass the bundle file or directory with -i, or the Steam directory with --steam.", e);
+                process::exit(1);
+            }
+        }
     }
-}
 
-impl Into<Unbundler> for CommandLine {
-    fn into(self) -> Unbundler {
-        let dds_mode = self.matches.get_count("dds") > 0;
-        let input_path;
+    fn profile(&self) -> Option<GameProfile> {
+        let game = self.matches.get_one::<String>("game")?;
 
-        match self.matches.get_one::<String>("input") {
-            Some(path) => input_path = String::from(path),
-            None => input_path = CommandLine::find_mww_bundles(),
+        match GameProfile::named(game) {
+            Ok(profile) => {
+                if !profile.verified {
+                    eprintln!("The {} profile has not been checked against the game's own bundles and may read them wrong.", profile.name);
+                }
+                Some(profile)
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
         }
+    }
+
+    //the names from --names, --wordlist and --table. the wordlist is merged into the names index.
+    pub fn dictionary(&self) -> Option<Dictionary> {
+        let index_path = self.matches.get_one::<String>("names").map(PathBuf::from);
+        let wordlist_path = self
+            .matches
+            .get_one::<String>("wordlist")
+            .map(PathBuf::from);
+        let tables = self.matches.get_one::<String>("table");
+        if index_path.is_none() && wordlist_path.is_none() && tables.is_none() {
+            return None;
+        }
+
+        let mut dictionary = match &index_path {
+            Some(index_path) => Dictionary::load_index(index_path)
+                .expect("The names index --names could not be read."),
+            None => Dictionary::default(),
+        };
+
+        if let Some(wordlist_path) = wordlist_path {
+            let added = dictionary
+                .add_wordlist(&wordlist_path)
+                .expect("The wordlist --wordlist could not be read.");
+            eprintln!("{} new names were read from {:?}", added, wordlist_path);
+        }
+
+        if let Some(index_path) = index_path {
+            dictionary
+                .save_index(&index_path)
+                .expect("The names index --names could not be written.");
+        }
+
+        for table_path in tables.into_iter().flat_map(|tables| tables.split(',')) {
+            match LookupTable::open(&PathBuf::from(table_path)) {
+                Ok(table) => dictionary.add_table(table),
+                Err(e) => {
+                    eprintln!("The lookup table {} could not be read: {}", table_path, e);
+                    process::exit(1);
+                }
+            }
+        }
+
+        Some(dictionary)
+    }
+
+    //the crack masks from --mask, with their wordlists read from --words.
+    pub fn masks(&self) -> Vec<Mask> {
+        let mut wordlists = HashMap::new();
+        if let Some(words) = self.matches.get_one::<String>("words") {
+            for wordlist in words.split(',') {
+                let (name, wordlist_path) = wordlist
+                    .split_once('=')
+                    .expect("--words must be a comma separated list of name=file pairs.");
+                let words = cracker::read_wordlist(&PathBuf::from(wordlist_path))
+                    .expect("A wordlist given with --words could not be read.");
+                wordlists.insert(name.to_string(), words);
+            }
+        }
+
+        let masks = match self.matches.get_one::<String>("mask") {
+            Some(masks) => masks.split(',').map(String::from).collect(),
+            None if wordlists.contains_key("suffixes") => vec![
+                "{dirs}/{stems}".to_string(),
+                "{dirs}/{stems}{suffixes}".to_string(),
+            ],
+            None => vec!["{dirs}/{stems}".to_string()],
+        };
+
+        masks
+            .iter()
+            .map(|mask| match Mask::parse(mask, &wordlists) {
+                Ok(mask) => mask,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            })
+            .collect()
+    }
+
+    //--type and --name see extensions as the --game profile names them.
+    fn file_filter(&self, profile: Option<&GameProfile>) -> FileFilter {
+        let extensions = match self.matches.get_one::<String>("type") {
+            Some(types) => FileFilter::parse_extensions(types, profile)
+                .expect("--type must be a comma separated list of known file types."),
+            None => vec![],
+        };
+
+        let paths = match self.matches.get_one::<String>("hash") {
+            Some(hashes) => FileFilter::parse_paths(hashes)
+                .expect("--hash must be a comma separated list of hex name hashes."),
+            None => vec![],
+        };
+
+        let names = match self.matches.get_one::<String>("name") {
+            Some(glob) => vec![glob.clone()],
+            None => vec![],
+        };
+
+        //the names are only needed to match --name against restored names.
+        let dictionary = if names.is_empty() {
+            None
+        } else {
+            self.dictionary().map(Arc::new)
+        };
 
+        FileFilter {
+            extensions,
+            paths,
+            names,
+            dictionary,
+            profile: profile.cloned(),
+        }
+    }
+}
+
+impl From<CommandLine> for Unbundler {
+    fn from(command_line: CommandLine) -> Unbundler {
+        let profile = command_line.profile();
         Unbundler {
-            file_path: PathBuf::from(input_path),
-            dds_mode,
+            file_path: PathBuf::from(command_line.input_path()),
+            filter: command_line.file_filter(profile.as_ref()),
+            profile,
+        }
+    }
+}
+
+impl From<CommandLine> for FileFilter {
+    fn from(command_line: CommandLine) -> FileFilter {
+        command_line.file_filter(command_line.profile().as_ref())
+    }
+}
+
+impl From<CommandLine> for Verifier {
+    fn from(command_line: CommandLine) -> Verifier {
+        Verifier {
+            file_path: PathBuf::from(command_line.input_path()),
+            profile: command_line.profile(),
         }
     }
 }
 
-impl Into<FileWriter> for CommandLine {
-    fn into(self) -> FileWriter {
-        if let Some(output_dir) = self.matches.get_one::<String>("output") {
-            FileWriter::new(PathBuf::from(output_dir))
+impl From<CommandLine> for ReportPrinter {
+    fn from(command_line: CommandLine) -> ReportPrinter {
+        let format = command_line.matches.get_one::<String>("format");
+        let format = match format.map(|f| f.as_str()) {
+            Some("json") => OutputFormat::Json,
+            Some("csv") => OutputFormat::Csv,
+            _ => OutputFormat::Text,
+        };
+
+        ReportPrinter { format }
+    }
+}
+
+impl From<CommandLine> for Bundler {
+    fn from(command_line: CommandLine) -> Bundler {
+        let file_path = command_line.matches.get_one::<String>("output")
+            .expect("The output -o argument for the bundler is required and is the path of the bundle to write.");
+
+        Bundler {
+            file_path: PathBuf::from(file_path),
+            profile: command_line.profile(),
+        }
+    }
+}
+
+impl From<CommandLine> for FileWriter {
+    fn from(command_line: CommandLine) -> FileWriter {
+        let matches = &command_line.matches;
+        let png_tool = matches
+            .get_one::<String>("tool")
+            .is_some_and(|tool| tool == "png");
+        let texture_mode = if png_tool || matches.get_count("png") > 0 {
+            TextureMode::Png {
+                mip: matches.get_one::<u32>("mip").copied().unwrap_or(0),
+                all_faces: matches.get_count("faces") > 0,
+            }
+        } else if matches.get_count("dds") > 0 {
+            TextureMode::Dds
+        } else {
+            TextureMode::Resource
+        };
+        let strings_format = matches
+            .get_one::<String>("strings")
+            .and_then(|format| StringsFormat::from_extension(format));
+        let names = command_line.dictionary();
+
+        if let Some(output_dir) = command_line.matches.get_one::<String>("output") {
+            FileWriter::new(
+                PathBuf::from(output_dir),
+                texture_mode,
+                strings_format,
+                names,
+            )
         } else {
             FileWriter::new(env::current_dir().expect(
                 "Attempted to default to current working directory for an output directory since no -o option was provided,
-                but either there is a lack of read permissions to the current directory or the current working directory does not exist."))
+                but either there is a lack of read permissions to the current directory or the current working directory does not exist."),
+                texture_mode, strings_format, names)
         }
     }
 }
 
-impl Into<Bootstrapper> for CommandLine {
-    fn into(self) -> Bootstrapper {
-        let compiler_path = self.matches.get_one::<String>("compiler")
+impl From<CommandLine> for Bootstrapper {
+    fn from(command_line: CommandLine) -> Bootstrapper {
+        let compiler_path = command_line.matches.get_one::<String>("compiler")
             .expect("When using the compiler bootstrap tool, you must supply an absolute path to the -c (-compiler) argument.")
             .to_string();
 
-        let src_dir = self.matches.get_one::<String>("input")
+        let src_dir = command_line.matches.get_one::<String>("input")
             .expect("The input -i argument for the compiler bootstrap tool is required for the -source-dir argument.")
             .to_string();
 
-        let data_dir = self.matches.get_one::<String>("data_dir")
+        let data_dir = command_line.matches.get_one::<String>("data_dir")
             .expect("The data directory -r argument for the compiler bootstrap tool is required for the -data-dir argument.")
             .to_string();
 
-        let bundle_dir = self.matches.get_one::<String>("output")
+        let bundle_dir = command_line.matches.get_one::<String>("output")
             .expect("The output -o argument for the compiler bootstrap tool is required for the -bundle-dir argument.")
             .to_string();
 
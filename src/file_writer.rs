@@ -1,49 +1,290 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{self, File};
 use std::io::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use bitsquid_unbundler::bundle_manifest::{BundleManifest, MANIFEST_NAME};
+use bitsquid_unbundler::extension_registry::{STRINGS, TEXTURE};
+use bitsquid_unbundler::strings::{StringTable, StringsFormat};
+use bitsquid_unbundler::texture::Texture;
 use bitsquid_unbundler::unbundled_file::UnbundledFile;
+use bitsquid_unbundler::unbundler::{Unbundler, UnbundlerError};
+use murmur32_gen::dictionary::Dictionary;
+
+use crate::progress::Progress;
+
+//how texture resources are written.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TextureMode {
+    Resource,
+    Dds,
+    Png { mip: u32, all_faces: bool }, //one mip level of the first face, or of every cubemap face.
+}
 
 pub struct FileWriter {
     output_directory: PathBuf,
-    count: usize,
+    texture_mode: TextureMode,
+    strings_format: Option<StringsFormat>, //strings resources are also exported as csv or json tables.
+    names: Option<Dictionary>,             //restores the original file and directory names.
+    unresolved: Mutex<BTreeSet<u64>>,      //name hashes the dictionary could not restore.
+    manifests: Mutex<BTreeMap<String, BundleManifest>>, //what the bundler needs besides the files.
 }
 
 impl FileWriter {
-    pub fn new(output_directory: PathBuf) -> FileWriter {
+    pub fn new(
+        output_directory: PathBuf,
+        texture_mode: TextureMode,
+        strings_format: Option<StringsFormat>,
+        names: Option<Dictionary>,
+    ) -> FileWriter {
         FileWriter {
             output_directory,
-            count: 1,
+            texture_mode,
+            strings_format,
+            names,
+            unresolved: Mutex::new(BTreeSet::new()),
+            manifests: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn write_file(&self, dir_name: &str, file: &UnbundledFile, progress: &Progress) {
+        let mut path = self.output_directory.clone();
+        path.push(dir_name);
+
+        let name = self.file_name(file);
+        let mut dir_path = path.clone();
+        dir_path.push(&name);
+        if let Err(e) = fs::create_dir_all(dir_path.parent().unwrap()) {
+            println!("IO Error: {}", e);
+        }
+
+        //a bundle is visited by one thread, so its files are listed in the bundle's order.
+        self.manifests
+            .lock()
+            .unwrap()
+            .entry(dir_name.to_string())
+            .or_default()
+            .push(file);
+
+        let texture_mode = match file.header.extension {
+            TEXTURE => self.texture_mode,
+            _ => TextureMode::Resource,
+        };
+        let extension = match texture_mode {
+            TextureMode::Resource => &file.extension,
+            TextureMode::Dds => "dds",
+            TextureMode::Png { .. } => "png",
+        };
+
+        //entries without variants are still written so that the bundle can be rebuilt.
+        if file.variants.is_empty() {
+            let mut file_path = path.clone();
+            file_path.push(format!("{}.{}", name, file.extension));
+            FileWriter::write_data(&file_path, &[]);
+        }
+
+        for variant in file.variants.iter() {
+            let mut file_path = path.clone();
+            file_path.push(file.variant_file_name(&name, variant, &file.extension));
+            FileWriter::write_data(&file_path, &variant.data);
+
+            //the image is written beside the texture, which is kept so the bundler can encode it back.
+            if texture_mode != TextureMode::Resource {
+                let image_path = path.join(file.variant_file_name(&name, variant, extension));
+                let converted = Texture::parse(&variant.data, &variant.stream)
+                    .and_then(|texture| self.convert_texture(&texture, &image_path));
+                match converted {
+                    Ok(images) => {
+                        for (image_path, image) in images {
+                            self.write_export(dir_name, &path, &image_path, &image);
+                        }
+                    }
+                    Err(e) => eprintln!("{}.{} could not be converted: {}", name, extension, e),
+                }
+            }
+
+            //the table is written beside the resource, which is kept so the bundler has its version.
+            if let (STRINGS, Some(format)) = (file.header.extension, self.strings_format) {
+                match StringTable::parse(&variant.data) {
+                    Ok(table) => {
+                        let empty = Dictionary::default();
+                        let names = self.names.as_ref().unwrap_or(&empty);
+                        let table_path =
+                            path.join(file.variant_file_name(&name, variant, format.extension()));
+                        let table = table.export(format, names);
+                        self.write_export(dir_name, &path, &table_path, table.as_bytes());
+                    }
+                    Err(e) => eprintln!("{}.{} could not be exported: {}", name, extension, e),
+                }
+            }
+
+            //streamed data is written beside the variant so the bundler can rebuild the .stream file.
+            if !variant.stream.is_empty() {
+                let mut stream_path = file_path.into_os_string();
+                stream_path.push(".stream");
+                FileWriter::write_data(&PathBuf::from(stream_path), &variant.stream);
+            }
+        }
+
+        progress.report(&format!("{}.{}", name, extension));
+    }
+
+    //the images to write for a texture and where they go.
+    //cubemap faces written as pngs get their own files: name.face0.png to name.face5.png.
+    fn convert_texture(
+        &self,
+        texture: &Texture,
+        file_path: &Path,
+    ) -> Result<Vec<(PathBuf, Vec<u8>)>, UnbundlerError> {
+        let (mip, all_faces) = match self.texture_mode {
+            TextureMode::Png { mip, all_faces } => (mip.min(texture.mip_count - 1), all_faces),
+            _ => return Ok(vec![(file_path.to_path_buf(), texture.to_dds())]),
+        };
+
+        if !(texture.cubemap && all_faces) {
+            return Ok(vec![(file_path.to_path_buf(), texture.to_png(0, mip)?)]);
+        }
+
+        (0..texture.faces())
+            .map(|face| {
+                let face_path = file_path.with_extension(format!("face{}.png", face));
+                Ok((face_path, texture.to_png(face, mip)?))
+            })
+            .collect()
+    }
+
+    //exported images and tables are listed in the manifest, so the bundler only imports edited ones.
+    fn write_export(&self, dir_name: &str, dir_path: &Path, file_path: &Path, data: &[u8]) {
+        FileWriter::write_data(file_path, data);
+
+        let Ok(relative_path) = file_path.strip_prefix(dir_path) else {
+            return;
+        };
+        let name: Vec<String> = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        self.manifests
+            .lock()
+            .unwrap()
+            .entry(dir_name.to_string())
+            .or_default()
+            .export(name.join("/"), data);
+    }
+
+    //converts a .dds file, or every .dds file in a directory, into pngs in the output directory.
+    pub fn convert_dds_files(&self, input: &Path) -> usize {
+        let mut dds_paths = vec![];
+        if input.is_dir() {
+            FileWriter::find_dds_files(input, &mut dds_paths);
+            dds_paths.sort();
+        } else {
+            dds_paths.push(input.to_path_buf());
+        }
+
+        let mut converted = 0;
+        for dds_path in dds_paths {
+            let relative_path = dds_path
+                .strip_prefix(input)
+                .ok()
+                .filter(|path| !path.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new(dds_path.file_name().unwrap()));
+            let mut png_path = self.output_directory.join(relative_path);
+            png_path.set_extension("png");
+            if let Err(e) = fs::create_dir_all(png_path.parent().unwrap()) {
+                println!("IO Error: {}", e);
+            }
+
+            let result = fs::read(&dds_path)
+                .map_err(UnbundlerError::from)
+                .and_then(|dds| Texture::from_dds(&dds))
+                .and_then(|texture| self.convert_texture(&texture, &png_path));
+            match result {
+                Ok(images) => {
+                    for (image_path, image) in images {
+                        FileWriter::write_data(&image_path, &image);
+                    }
+                    converted += 1;
+                }
+                Err(e) => eprintln!("{:?} could not be converted: {}", dds_path, e),
+            }
+        }
+        converted
+    }
+
+    fn find_dds_files(dir_path: &Path, dds_paths: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir_path) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                FileWriter::find_dds_files(&path, dds_paths);
+            } else if path.extension().is_some_and(|ext| ext == "dds") {
+                dds_paths.push(path);
+            }
         }
     }
 
-    pub fn write_files(&mut self, unbundled_dir: &UnbundledDirectory, total: usize) {
-        for file in unbundled_dir.files.iter() {
-            let mut path = self.output_directory.clone();
-            path.push(format!(r"{}\", unbundled_dir.dir_name));
+    //the restored name, such as scripts/game/player, or the hex name if it is not in the dictionary.
+    fn file_name(&self, file: &UnbundledFile) -> String {
+        let names = match &self.names {
+            Some(names) => names,
+            None => return file.hex_name(),
+        };
 
-            match fs::create_dir(&path) {
-                Ok(_) => (),
-                Err(_) => (),
+        match names.lookup(file.path) {
+            Some(name) => name.to_string(),
+            None => {
+                self.unresolved.lock().unwrap().insert(file.path);
+                file.hex_name()
             }
+        }
+    }
 
-            path.push(format!("{:#x}.{}", file.path, file.extension));
-            self.write_file(&path, file, total);
+    //lists the name hashes which are still unknown, so that they can be searched for.
+    pub fn write_unresolved(&self) {
+        if self.names.is_none() {
+            return;
+        }
+
+        let mut text = String::new();
+        for hash in self.unresolved.lock().unwrap().iter() {
+            text.push_str(&format!("{:#018x}\n", hash));
+        }
+
+        let mut file_path = self.output_directory.clone();
+        file_path.push("names_unresolved.txt");
+        FileWriter::write_data(&file_path, text.as_bytes());
+    }
+
+    //saves bundle.json beside the files of every bundle, with the header the files do not hold.
+    pub fn write_manifests(&self, unbundler: &Unbundler) {
+        let mut manifests = self.manifests.lock().unwrap();
+        for bundle_path in unbundler.bundle_paths().unwrap_or_default() {
+            let Some(dir_name) = bundle_path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let (Some(manifest), Ok(bundle_reader)) = (
+                manifests.get_mut(dir_name),
+                unbundler.read_bundle(&bundle_path),
+            ) else {
+                continue;
+            };
+
+            manifest.set_header(&bundle_reader.header);
+            let manifest_path = self.output_directory.join(dir_name).join(MANIFEST_NAME);
+            if let Err(e) = manifest.save(&manifest_path) {
+                println!("IO Error: {}", e);
+            }
         }
     }
 
-    fn write_file(&mut self, file_path: &PathBuf, unbundled_file: &UnbundledFile, total: usize) {
+    fn write_data(file_path: &Path, data: &[u8]) {
         match File::create(file_path) {
             Ok(mut file) => {
-                file.write_all(&unbundled_file.data).unwrap();
-                println!(
-                    "[{}/{}] {}",
-                    self.count,
-                    total,
-                    file_path.file_name().unwrap().to_str().unwrap()
-                );
-                self.count += 1;
+                file.write_all(data).unwrap();
             }
             Err(e) => {
                 println!("IO Error: {}", e);
@@ -1,6 +1,18 @@
-use bitsquid_unbundler::{unbundled_directory::UnbundledDirectory, unbundler::Unbundler};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use bitsquid_unbundler::bundle_diff::BundleDiff;
+use bitsquid_unbundler::extension_registry::ExtensionRegistry;
+use bitsquid_unbundler::file_filter::FileFilter;
+use bitsquid_unbundler::name_harvester::NameHarvester;
+use bitsquid_unbundler::package::PackageGraph;
+use bitsquid_unbundler::resource_index::ResourceIndex;
+use bitsquid_unbundler::{bundler::Bundler, unbundler::Unbundler, verifier::Verifier};
 use command_line::CommandLine;
 use file_writer::FileWriter;
+use murmur32_gen::cracker::Cracker;
+use progress::Progress;
+use report_printer::ReportPrinter;
 
 use compiler_bootstrap::bootstrap::Bootstrapper;
 
@@ -10,6 +22,8 @@ extern crate compiler_bootstrap;
 
 mod command_line;
 mod file_writer;
+mod progress;
+mod report_printer;
 
 fn main() {
     let cmd = CommandLine::new();
@@ -20,16 +34,198 @@ fn main() {
         .expect("-t argument was not given.")
         .clone();
 
+    let unknown_extensions = cmd.matches.get_one::<String>("unknown_extensions").cloned();
+    if let Some(extensions) = cmd.matches.get_one::<String>("extensions") {
+        ExtensionRegistry::global()
+            .load(&PathBuf::from(extensions))
+            .expect("The extensions -e file could not be read.");
+    }
+
     match tool.as_str() {
         "bitsquid_unbundler" => {
-            let unbundler: &mut Unbundler = &mut cmd.clone().into();
-            let unbundled = unbundler.unbundle().unwrap();
-            let total = get_total_files(&unbundled);
-            let file_writer: &mut FileWriter = &mut cmd.clone().into();
+            let unbundler: &Unbundler = &cmd.clone().into();
+            let progress = Progress::new(unbundler.count_files().unwrap());
+            let file_writer: &FileWriter = &cmd.clone().into();
+            let jobs = cmd.matches.get_one::<usize>("jobs").copied().unwrap_or(1);
+
+            unbundler
+                .for_each_file_parallel(jobs, |dir_name, file| {
+                    file_writer.write_file(dir_name, &file, &progress)
+                })
+                .unwrap();
+            file_writer.write_manifests(unbundler);
+            file_writer.write_unresolved();
+        }
+        "bitsquid_bundler" => {
+            let input = PathBuf::from(cmd.matches.get_one::<String>("input").expect(
+                "The input -i argument for the bundler is required and is a directory written by the unbundler.",
+            ));
+            let bundler: &Bundler = &cmd.into();
+            bundler.bundle_directory(&input).unwrap();
+        }
+        "verify" => {
+            let verifier: &Verifier = &cmd.clone().into();
+            let reports = verifier.verify().unwrap();
+            let printer: &ReportPrinter = &cmd.into();
+            printer.print_verify_reports(&reports);
+
+            if reports.iter().any(|report| !report.passed) {
+                std::process::exit(1);
+            }
+        }
+        "list" => {
+            let unbundler: &Unbundler = &cmd.clone().into();
+            let printer: &ReportPrinter = &cmd.into();
+            printer.print_index(&unbundler.index().unwrap());
+        }
+        "where" => {
+            let index_path = PathBuf::from(cmd.matches.get_one::<String>("index").expect(
+                "The index -x argument for the where tool is required and is the path of the reverse index.",
+            ));
 
-            for unbundled_dir in unbundled {
-                file_writer.write_files(&unbundled_dir, total);
+            //with an input the index is rebuilt from the bundles, otherwise the saved index is queried.
+            let index = if cmd.matches.get_one::<String>("input").is_some() {
+                let mut unbundler: Unbundler = cmd.clone().into();
+                unbundler.filter = FileFilter::default();
+                let jobs = cmd.matches.get_one::<usize>("jobs").copied().unwrap_or(1);
+
+                let index = ResourceIndex::build(&unbundler, jobs).unwrap();
+                index.save(&index_path).unwrap();
+                index
+            } else {
+                ResourceIndex::load(&index_path).unwrap()
+            };
+
+            //without a filter, report every resource whose copies are not identical.
+            let filter: FileFilter = cmd.clone().into();
+            let printer: &ReportPrinter = &cmd.into();
+            if filter.is_empty() {
+                printer.print_locations(&index.mismatched());
+            } else {
+                printer.print_locations(&index.select(&filter));
+            }
+        }
+        "diff" => {
+            let old: Unbundler = cmd.clone().into();
+            let mut new = old.clone();
+            new.file_path = PathBuf::from(cmd.matches.get_one::<String>("new").expect(
+                "The --new argument for the diff tool is required and is the bundle or directory to compare the input against.",
+            ));
+            let jobs = cmd.matches.get_one::<usize>("jobs").copied().unwrap_or(1);
+
+            let printer: &ReportPrinter = &cmd.into();
+            printer.print_diff(&BundleDiff::diff(&old, &new, jobs).unwrap());
+        }
+        "harvest" => {
+            let unbundler: &Unbundler = &cmd.clone().into();
+            let jobs = cmd.matches.get_one::<usize>("jobs").copied().unwrap_or(1);
+
+            let harvester = NameHarvester::new(unbundler).unwrap();
+            let names = harvester.harvest(unbundler, jobs).unwrap();
+
+            //the harvested names grow the names index, so the next unbundling restores them.
+            if let Some(mut dictionary) = cmd.dictionary() {
+                let added = dictionary.add_all(names.iter().map(|name| name.name.as_str()));
+                eprintln!("{} of the harvested names are new.", added);
+
+                if let Some(index_path) = cmd.matches.get_one::<String>("names") {
+                    dictionary
+                        .save_index(&PathBuf::from(index_path))
+                        .expect("The names index --names could not be written.");
+                }
             }
+
+            let printer: &ReportPrinter = &cmd.into();
+            printer.print_harvest(&names);
+        }
+        "crack" => {
+            let unbundler: &Unbundler = &cmd.clone().into();
+            let masks = cmd.masks();
+            let mut dictionary = cmd.dictionary().unwrap_or_default();
+
+            //only the names which are not known yet are searched for.
+            let mut targets = HashSet::new();
+            for index in unbundler.index().unwrap() {
+                for entry in index.entries {
+                    if dictionary.lookup(entry.path).is_none() {
+                        targets.insert(entry.path);
+                    }
+                }
+            }
+
+            let mut cracker = Cracker::new(targets);
+            if let Some(jobs) = cmd.matches.get_one::<usize>("jobs") {
+                cracker.jobs = *jobs;
+            }
+            cracker.progress_path = cmd.matches.get_one::<String>("progress").map(PathBuf::from);
+
+            let keyspace: u64 = masks.iter().map(|mask| mask.keyspace()).sum();
+            eprintln!(
+                "Searching {} candidates for {} unresolved names on {} threads.",
+                keyspace,
+                cracker.targets.len(),
+                cracker.jobs
+            );
+
+            let hits = cracker
+                .crack(&masks, |hash, name| println!("{:#018x} {}", hash, name))
+                .unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                });
+            eprintln!("{} names found.", hits.len());
+
+            if let Some(index_path) = cmd.matches.get_one::<String>("names") {
+                dictionary.add_all(hits.values().map(|name| name.as_str()));
+                dictionary
+                    .save_index(&PathBuf::from(index_path))
+                    .expect("The names index --names could not be written.");
+            }
+        }
+        "packages" => {
+            let unbundler: &Unbundler = &cmd.clone().into();
+            let names = cmd.dictionary().unwrap_or_default();
+            let jobs = cmd.matches.get_one::<usize>("jobs").copied().unwrap_or(1);
+
+            let printer: &ReportPrinter = &cmd.into();
+            printer.print_packages(&PackageGraph::build(unbundler, &names, jobs).unwrap());
+        }
+        "table" => {
+            let output = PathBuf::from(cmd.matches.get_one::<String>("output").expect(
+                "The output -o argument for the table tool is required and is the lookup table to write, or a .txt file to export it as text.",
+            ));
+            let mut dictionary = cmd.dictionary().expect(
+                "The table tool needs names to write. Give them with --names, --wordlist or --table.",
+            );
+
+            //merging tables from several people is where the same hash is most likely given two names.
+            let table = dictionary
+                .to_table()
+                .expect("The names do not fit in a lookup table.");
+            for collision in dictionary.collisions() {
+                eprintln!(
+                    "Collision: {:#018x} is both {} and {}. {} was kept.",
+                    collision.hash, collision.kept, collision.other, collision.kept
+                );
+            }
+
+            if output.extension().is_some_and(|ext| ext == "txt") {
+                std::fs::write(&output, table.export_text())
+            } else {
+                table.save(&output)
+            }
+            .expect("The lookup table could not be written.");
+            eprintln!("{} names were written to {:?}", table.len(), output);
+        }
+        "png" => {
+            let input = PathBuf::from(cmd.matches.get_one::<String>("input").expect(
+                "The input -i argument for the png tool is required and is a .dds file or a directory of them.",
+            ));
+            let file_writer: &FileWriter = &cmd.into();
+            eprintln!(
+                "{} textures were converted.",
+                file_writer.convert_dds_files(&input)
+            );
         }
         "compiler_bootstrap" => {
             let bootstrapper: &Bootstrapper = &cmd.into();
@@ -40,8 +236,12 @@ fn main() {
         "luajit_decompiler" => (), //soon^tm
         _ => panic!("Unknown tool (-t). Please see the supported tools with the --help command."),
     }
-}
 
-fn get_total_files(unbundled: &Vec<UnbundledDirectory>) -> usize {
-    unbundled.iter().fold(0, |sum, val| sum + val.files.len())
+    if let Some(unknown_extensions) = unknown_extensions {
+        std::fs::write(
+            unknown_extensions,
+            ExtensionRegistry::global().unknown_table(),
+        )
+        .expect("The unknown extensions file could not be written.");
+    }
 }
@@ -0,0 +1,21 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//counts finished work items across threads and prints a [count/total] line for each.
+pub struct Progress {
+    count: AtomicUsize,
+    total: usize,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Progress {
+        Progress {
+            count: AtomicUsize::new(0),
+            total,
+        }
+    }
+
+    pub fn report(&self, name: &str) {
+        let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+        println!("[{}/{}] {}", count, self.total, name);
+    }
+}
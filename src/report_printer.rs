@@ -0,0 +1,283 @@
+use bitsquid_unbundler::bundle_diff::{BundleDiff, ChangeKind};
+use bitsquid_unbundler::bundle_index::BundleIndex;
+use bitsquid_unbundler::name_harvester::HarvestedName;
+use bitsquid_unbundler::package::PackageGraph;
+use bitsquid_unbundler::resource_index::ResourceLocation;
+use bitsquid_unbundler::verifier::BundleReport;
+use serde::Serialize;
+
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+pub struct ReportPrinter {
+    pub format: OutputFormat,
+}
+
+impl ReportPrinter {
+    pub fn print_verify_reports(&self, reports: &[BundleReport]) {
+        if let OutputFormat::Json = self.format {
+            return ReportPrinter::print_json(reports);
+        }
+
+        for report in reports.iter() {
+            let status = if report.passed { "PASS" } else { "FAIL" };
+            println!(
+                "{} {} ({} files, {} bytes inflated)",
+                status, report.bundle_name, report.file_count, report.inflated_size
+            );
+
+            for error in report.errors.iter() {
+                println!("    {}", error);
+            }
+        }
+
+        let passed = reports.iter().filter(|report| report.passed).count();
+        println!("{}/{} bundles passed verification.", passed, reports.len());
+    }
+
+    pub fn print_index(&self, indices: &[BundleIndex]) {
+        match self.format {
+            OutputFormat::Json => ReportPrinter::print_json(indices),
+            OutputFormat::Csv => {
+                println!("bundle,extension_hash,extension,path,variant_count,size");
+                for index in indices.iter() {
+                    for entry in index.entries.iter() {
+                        println!(
+                            "{},{:#x},{},{:#x},{},{}",
+                            index.bundle_name,
+                            entry.extension_hash,
+                            entry.extension,
+                            entry.path,
+                            entry.variant_count,
+                            entry.size
+                        );
+                    }
+                }
+            }
+            OutputFormat::Text => {
+                println!(
+                    "{:<24} {:<20} {:<20} {:<20} {:>8} {:>12}",
+                    "bundle", "extension hash", "extension", "path", "variants", "size"
+                );
+                for index in indices.iter() {
+                    for entry in index.entries.iter() {
+                        println!(
+                            "{:<24} {:<20} {:<20} {:<20} {:>8} {:>12}",
+                            index.bundle_name,
+                            format!("{:#x}", entry.extension_hash),
+                            entry.extension,
+                            format!("{:#x}", entry.path),
+                            entry.variant_count,
+                            entry.size
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn print_locations(&self, locations: &[&ResourceLocation]) {
+        match self.format {
+            OutputFormat::Json => ReportPrinter::print_json(locations),
+            OutputFormat::Csv => {
+                println!("extension_hash,extension,path,bundle,digest,size,identical");
+                for location in locations.iter() {
+                    for copy in location.copies.iter() {
+                        println!(
+                            "{:#x},{},{:#x},{},{:#x},{},{}",
+                            location.extension_hash,
+                            location.extension,
+                            location.path,
+                            copy.bundle_name,
+                            copy.digest,
+                            copy.size,
+                            location.identical()
+                        );
+                    }
+                }
+            }
+            OutputFormat::Text => {
+                for location in locations.iter() {
+                    let status = if location.identical() {
+                        "identical"
+                    } else {
+                        "DIFFERENT"
+                    };
+                    println!(
+                        "{:#x}.{} ({} copies, {})",
+                        location.path,
+                        location.extension,
+                        location.copies.len(),
+                        status
+                    );
+
+                    for copy in location.copies.iter() {
+                        println!(
+                            "    {:<24} {:<20} {:>12}",
+                            copy.bundle_name,
+                            format!("{:#x}", copy.digest),
+                            copy.size
+                        );
+                    }
+                }
+                println!("{} resources.", locations.len());
+            }
+        }
+    }
+
+    pub fn print_diff(&self, diff: &BundleDiff) {
+        match self.format {
+            OutputFormat::Json => ReportPrinter::print_json(diff),
+            OutputFormat::Csv => {
+                println!("change,extension_hash,extension,path,old_size,new_size,size_delta,first_difference,differing_bytes");
+                for change in diff.changes.iter() {
+                    let bytes = change.bytes.as_ref();
+                    println!(
+                        "{},{:#x},{},{:#x},{},{},{},{},{}",
+                        ReportPrinter::change_name(change.kind),
+                        change.extension_hash,
+                        change.extension,
+                        change.path,
+                        ReportPrinter::optional(change.old_size),
+                        ReportPrinter::optional(change.new_size),
+                        change.size_delta,
+                        ReportPrinter::optional(bytes.and_then(|bytes| bytes.first_difference)),
+                        ReportPrinter::optional(bytes.map(|bytes| bytes.differing_bytes))
+                    );
+                }
+            }
+            OutputFormat::Text => {
+                for change in diff.changes.iter() {
+                    let name = format!("{:#x}.{}", change.path, change.extension);
+                    match change.kind {
+                        ChangeKind::Added => {
+                            println!("+ {} ({} bytes)", name, change.new_size.unwrap_or(0))
+                        }
+                        ChangeKind::Removed => {
+                            println!("- {} ({} bytes)", name, change.old_size.unwrap_or(0))
+                        }
+                        ChangeKind::Modified => {
+                            print!(
+                                "~ {} ({} -> {} bytes, {:+})",
+                                name,
+                                change.old_size.unwrap_or(0),
+                                change.new_size.unwrap_or(0),
+                                change.size_delta
+                            );
+                            match change.bytes.as_ref() {
+                                Some(bytes) => println!(
+                                    ", {} bytes differ from offset {:#x}",
+                                    bytes.differing_bytes,
+                                    bytes.first_difference.unwrap_or(0)
+                                ),
+                                None => println!(),
+                            }
+                        }
+                    }
+                }
+
+                let count = |kind| diff.changes.iter().filter(|c| c.kind == kind).count();
+                println!(
+                    "{} added, {} removed, {} modified.",
+                    count(ChangeKind::Added),
+                    count(ChangeKind::Removed),
+                    count(ChangeKind::Modified)
+                );
+            }
+        }
+    }
+
+    pub fn print_harvest(&self, names: &[HarvestedName]) {
+        match self.format {
+            OutputFormat::Json => ReportPrinter::print_json(names),
+            OutputFormat::Csv => {
+                println!("path,name,extensions");
+                for name in names.iter() {
+                    println!(
+                        "{:#x},{},{}",
+                        name.path,
+                        name.name,
+                        name.extensions.join(" ")
+                    );
+                }
+            }
+            OutputFormat::Text => {
+                for name in names.iter() {
+                    println!(
+                        "{:#018x} {} ({})",
+                        name.path,
+                        name.name,
+                        name.extensions.join(", ")
+                    );
+                }
+                println!("{} names harvested.", names.len());
+            }
+        }
+    }
+
+    //json gives the whole graph, csv one package to bundle edge per line.
+    pub fn print_packages(&self, graph: &PackageGraph) {
+        match self.format {
+            OutputFormat::Json => ReportPrinter::print_json(graph),
+            OutputFormat::Csv => {
+                println!("package,bundle,resources");
+                for package in graph.packages.iter() {
+                    let name = package
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("{:#x}", package.path));
+                    for (bundle, resources) in package.dependencies.iter() {
+                        println!("{},{},{}", name, bundle, resources);
+                    }
+                }
+            }
+            OutputFormat::Text => {
+                for package in graph.packages.iter() {
+                    let name = package
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("{:#x}", package.path));
+                    println!("{} (in {})", name, package.bundles.join(", "));
+
+                    let dependencies: Vec<String> = package
+                        .dependencies
+                        .iter()
+                        .map(|(bundle, resources)| format!("{} ({})", bundle, resources))
+                        .collect();
+                    println!("    needs: {}", dependencies.join(", "));
+
+                    for entry in package.entries.iter() {
+                        println!("    {}", entry);
+                    }
+                    for entry in package.missing.iter() {
+                        println!("    missing: {}", entry);
+                    }
+                }
+                println!("{} packages.", graph.packages.len());
+            }
+        }
+    }
+
+    fn change_name(kind: ChangeKind) -> &'static str {
+        match kind {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Modified => "modified",
+        }
+    }
+
+    //empty csv fields for values which do not apply.
+    fn optional(value: Option<u64>) -> String {
+        value.map(|value| value.to_string()).unwrap_or_default()
+    }
+
+    fn print_json<T: Serialize + ?Sized>(value: &T) {
+        match serde_json::to_string_pretty(value) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("Failed to serialize the report as json: {}", e),
+        }
+    }
+}
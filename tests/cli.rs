@@ -0,0 +1,450 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use bitsquid_unbundler::bundle_header::{BundleEntryHeader, BundleHeader};
+use bitsquid_unbundler::bundler::Bundler;
+use bitsquid_unbundler::extension_registry::{STRINGS, TEXTURE};
+use bitsquid_unbundler::file_filter::FileFilter;
+use bitsquid_unbundler::game_profile::GameProfile;
+use bitsquid_unbundler::texture::{Texture, TextureFormat};
+use bitsquid_unbundler::unbundled_directory::UnbundledDirectory;
+use bitsquid_unbundler::unbundled_file::{ResourceVariant, UnbundledFile};
+use bitsquid_unbundler::unbundler::Unbundler;
+use game_discovery::known_games::KNOWN_GAMES;
+
+fn temp_path(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("bitsquid_cli_{}_{}", name, std::process::id()));
+    path
+}
+
+fn run(args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_bitsquid_re_tools"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{:?} failed:\n{}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn lua_file(path: u64, data: &[u8]) -> UnbundledFile {
+    UnbundledFile {
+        path,
+        extension: "lua".to_string(),
+        variants: vec![ResourceVariant {
+            language: 0,
+            flags: 0,
+            data: data.to_vec(),
+            stream: vec![],
+        }],
+        header: BundleEntryHeader {
+            extension: 0xa14e8dfa2cd117e2,
+            path,
+            variant_count: 1,
+            reserved: 0,
+        },
+    }
+}
+
+//an uncompressed texture, which survives being written as a png unchanged.
+fn texture_file(path: u64) -> UnbundledFile {
+    let texture = Texture {
+        flags: 3,
+        format: TextureFormat::Rgba8,
+        width: 4,
+        height: 2,
+        depth: 1,
+        mip_count: 1,
+        cubemap: false,
+        data: (0..32).collect(),
+    };
+    UnbundledFile {
+        path,
+        extension: "texture".to_string(),
+        variants: vec![ResourceVariant {
+            language: 0,
+            flags: 0,
+            data: texture.to_resource(),
+            stream: vec![],
+        }],
+        header: BundleEntryHeader {
+            extension: TEXTURE,
+            path,
+            variant_count: 1,
+            reserved: 0,
+        },
+    }
+}
+
+//a compressed texture with mips, whose largest mip is in the .stream file.
+fn streamed_texture_file(path: u64, format: TextureFormat) -> UnbundledFile {
+    let pixels: Vec<u8> = (0..8 * 8 * 4).map(|i| (i * 7) as u8).collect();
+    let texture = Texture {
+        flags: 0x55,
+        ..Texture::from_rgba(format, 8, 8, pixels, true).unwrap()
+    };
+    let dds = texture.to_dds();
+    let top_mip = texture.format.surface_size(8, 8, 1).unwrap();
+    let mut embedded = dds[..128].to_vec();
+    embedded.extend(&dds[128 + top_mip..]);
+
+    let mut data = vec![];
+    data.extend(texture.flags.to_le_bytes());
+    data.extend((embedded.len() as u32).to_le_bytes());
+    data.extend(&embedded);
+    data.extend(1u32.to_le_bytes());
+    data.extend(0u32.to_le_bytes());
+    data.extend((top_mip as u32).to_le_bytes());
+    UnbundledFile {
+        path,
+        extension: "texture".to_string(),
+        variants: vec![ResourceVariant {
+            language: 0,
+            flags: top_mip as u32,
+            data,
+            stream: dds[128..128 + top_mip].to_vec(),
+        }],
+        header: BundleEntryHeader {
+            extension: TEXTURE,
+            path,
+            variant_count: 1,
+            reserved: 0,
+        },
+    }
+}
+
+#[test]
+fn untouched_images_leave_compressed_textures_as_they_were() {
+    for mode in ["--png", "-d"] {
+        let bundle_path = temp_path(&format!("untouched{}", mode));
+        let output_path = temp_path(&format!("untouched{}_dir", mode));
+        let rebuilt_path = temp_path(&format!("untouched{}_rebuilt", mode));
+        let files = vec![
+            streamed_texture_file(0x1234, TextureFormat::Bc1),
+            streamed_texture_file(0x5678, TextureFormat::Bc3),
+        ];
+        Bundler {
+            file_path: bundle_path.clone(),
+            profile: None,
+        }
+        .bundle(&UnbundledDirectory::new(
+            "untouched".to_string(),
+            BundleHeader::default(),
+            files,
+        ))
+        .unwrap();
+
+        run(&[
+            "-t",
+            "bitsquid_unbundler",
+            mode,
+            "-i",
+            bundle_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ]);
+        let dir_path = output_path.join(bundle_path.file_name().unwrap());
+        run(&[
+            "-t",
+            "bitsquid_bundler",
+            "-i",
+            dir_path.to_str().unwrap(),
+            "-o",
+            rebuilt_path.to_str().unwrap(),
+        ]);
+        assert_eq!(
+            fs::read(&bundle_path).unwrap(),
+            fs::read(&rebuilt_path).unwrap()
+        );
+        assert_eq!(
+            fs::read(Unbundler::stream_path(&bundle_path)).unwrap(),
+            fs::read(Unbundler::stream_path(&rebuilt_path)).unwrap()
+        );
+
+        fs::remove_file(Unbundler::stream_path(&bundle_path)).unwrap();
+        fs::remove_file(Unbundler::stream_path(&rebuilt_path)).unwrap();
+        fs::remove_file(bundle_path).unwrap();
+        fs::remove_file(rebuilt_path).unwrap();
+        fs::remove_dir_all(output_path).unwrap();
+    }
+}
+
+#[test]
+fn directories_unbundled_with_converted_textures_can_be_bundled_again() {
+    for (mode, image) in [("--png", "png"), ("-d", "dds")] {
+        let bundle_path = temp_path(&format!("converted{}", mode));
+        let output_path = temp_path(&format!("converted{}_dir", mode));
+        let rebuilt_path = temp_path(&format!("converted{}_rebuilt", mode));
+        let files = vec![texture_file(0x5678), lua_file(0x1234, b"return 1")];
+        let original =
+            UnbundledDirectory::new("converted".to_string(), BundleHeader::default(), files);
+        Bundler {
+            file_path: bundle_path.clone(),
+            profile: None,
+        }
+        .bundle(&original)
+        .unwrap();
+
+        run(&[
+            "-t",
+            "bitsquid_unbundler",
+            mode,
+            "-i",
+            bundle_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ]);
+        let dir_path = output_path.join(bundle_path.file_name().unwrap());
+        assert!(dir_path.join("0x5678.texture").exists());
+        assert!(dir_path.join(format!("0x5678.{}", image)).exists());
+        run(&[
+            "-t",
+            "bitsquid_bundler",
+            "-i",
+            dir_path.to_str().unwrap(),
+            "-o",
+            rebuilt_path.to_str().unwrap(),
+        ]);
+
+        let rebuilt = Unbundler {
+            file_path: rebuilt_path.clone(),
+            filter: FileFilter::default(),
+            profile: None,
+        }
+        .unbundle()
+        .unwrap()
+        .remove(0);
+        let mut paths: Vec<u64> = rebuilt.files.iter().map(|file| file.path).collect();
+        paths.sort();
+        assert_eq!(paths, vec![0x1234, 0x5678]);
+        for file in original.files.iter() {
+            let read_back = rebuilt
+                .files
+                .iter()
+                .find(|read_back| read_back.path == file.path);
+            assert_eq!(read_back.unwrap().variants[0].data, file.variants[0].data);
+        }
+
+        //an edited image is still imported.
+        let edited = Texture {
+            data: vec![0xff; 32],
+            ..Texture::parse(&original.files[0].variants[0].data, &[]).unwrap()
+        };
+        let edited_image = match image {
+            "png" => edited.to_png(0, 0).unwrap(),
+            _ => edited.to_dds(),
+        };
+        fs::write(dir_path.join(format!("0x5678.{}", image)), edited_image).unwrap();
+        run(&[
+            "-t",
+            "bitsquid_bundler",
+            "-i",
+            dir_path.to_str().unwrap(),
+            "-o",
+            rebuilt_path.to_str().unwrap(),
+        ]);
+        let rebuilt = Unbundler {
+            file_path: rebuilt_path.clone(),
+            filter: FileFilter::default(),
+            profile: None,
+        }
+        .unbundle()
+        .unwrap()
+        .remove(0);
+        let texture = rebuilt
+            .files
+            .iter()
+            .find(|file| file.path == 0x5678)
+            .unwrap();
+        assert_eq!(
+            Texture::parse(&texture.variants[0].data, &[]).unwrap().data,
+            edited.data
+        );
+
+        fs::remove_file(bundle_path).unwrap();
+        fs::remove_file(rebuilt_path).unwrap();
+        fs::remove_dir_all(output_path).unwrap();
+    }
+}
+
+//two keys share the offset of one value, which a table written back would not do.
+fn strings_file(path: u64) -> UnbundledFile {
+    let mut data = vec![];
+    data.extend(3u32.to_le_bytes());
+    data.extend(3u32.to_le_bytes());
+    for (key, offset) in [(0x11111111u32, 0u32), (0x22222222, 6), (0x33333333, 0)] {
+        data.extend(key.to_le_bytes());
+        data.extend(offset.to_le_bytes());
+    }
+    data.extend(b"Hello\0World\0");
+    UnbundledFile {
+        path,
+        extension: "strings".to_string(),
+        variants: vec![ResourceVariant {
+            language: 2,
+            flags: 0,
+            data,
+            stream: vec![],
+        }],
+        header: BundleEntryHeader {
+            extension: STRINGS,
+            path,
+            variant_count: 1,
+            reserved: 0,
+        },
+    }
+}
+
+#[test]
+fn untouched_tables_leave_strings_as_they_were() {
+    for format in ["csv", "json"] {
+        let bundle_path = temp_path(&format!("tables_{}", format));
+        let output_path = temp_path(&format!("tables_{}_dir", format));
+        let rebuilt_path = temp_path(&format!("tables_{}_rebuilt", format));
+        Bundler {
+            file_path: bundle_path.clone(),
+            profile: None,
+        }
+        .bundle(&UnbundledDirectory::new(
+            "tables".to_string(),
+            BundleHeader::default(),
+            vec![strings_file(0x1234)],
+        ))
+        .unwrap();
+
+        run(&[
+            "-t",
+            "bitsquid_unbundler",
+            "--strings",
+            format,
+            "-i",
+            bundle_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ]);
+        let dir_path = output_path.join(bundle_path.file_name().unwrap());
+        assert!(dir_path.join(format!("0x1234.lang_02.{}", format)).exists());
+        run(&[
+            "-t",
+            "bitsquid_bundler",
+            "-i",
+            dir_path.to_str().unwrap(),
+            "-o",
+            rebuilt_path.to_str().unwrap(),
+        ]);
+        assert_eq!(
+            fs::read(&bundle_path).unwrap(),
+            fs::read(&rebuilt_path).unwrap()
+        );
+
+        fs::remove_file(bundle_path).unwrap();
+        fs::remove_file(rebuilt_path).unwrap();
+        fs::remove_dir_all(output_path).unwrap();
+    }
+}
+
+#[test]
+fn unbundled_directories_are_bundled_back_into_the_same_bundle() {
+    let bundle_path = temp_path("same_bundle");
+    let output_path = temp_path("same_bundle_dir");
+    let rebuilt_path = temp_path("same_bundle_rebuilt");
+
+    //the checksum, version and flags are not in the file names, and the files are not in name order.
+    let mut files = vec![lua_file(0x5678, b"return 2"), lua_file(0x1234, b"return 1")];
+    files[0].variants[0].flags = 0x42;
+    files[1].header.reserved = 7;
+    let header = BundleHeader {
+        version: 0xf0000006,
+        checksum: (0..=255).collect(),
+        ..BundleHeader::default()
+    };
+    Bundler {
+        file_path: bundle_path.clone(),
+        profile: None,
+    }
+    .bundle(&UnbundledDirectory::new("same".to_string(), header, files))
+    .unwrap();
+
+    run(&[
+        "-t",
+        "bitsquid_unbundler",
+        "-i",
+        bundle_path.to_str().unwrap(),
+        "-o",
+        output_path.to_str().unwrap(),
+    ]);
+    let dir_path = output_path.join(bundle_path.file_name().unwrap());
+    run(&[
+        "-t",
+        "bitsquid_bundler",
+        "-i",
+        dir_path.to_str().unwrap(),
+        "-o",
+        rebuilt_path.to_str().unwrap(),
+    ]);
+    assert_eq!(
+        fs::read(&bundle_path).unwrap(),
+        fs::read(&rebuilt_path).unwrap()
+    );
+
+    fs::remove_file(bundle_path).unwrap();
+    fs::remove_file(rebuilt_path).unwrap();
+    fs::remove_dir_all(output_path).unwrap();
+}
+
+#[test]
+fn every_known_game_can_be_given_with_game() {
+    for game in KNOWN_GAMES.iter() {
+        let bundle_path = temp_path(&format!("game_{}", game.id));
+        let files = vec![lua_file(0x1234, b"return 1")];
+        Bundler {
+            file_path: bundle_path.clone(),
+            profile: Some(GameProfile::named(game.id).unwrap()),
+        }
+        .bundle(&UnbundledDirectory::new(
+            game.id.to_string(),
+            BundleHeader::default(),
+            files,
+        ))
+        .unwrap();
+
+        let output = run(&[
+            "-t",
+            "list",
+            "-f",
+            "csv",
+            "-g",
+            game.id,
+            "-i",
+            bundle_path.to_str().unwrap(),
+        ]);
+        assert!(String::from_utf8_lossy(&output.stdout).contains(",lua,0x1234,1,8"));
+        //unverified profiles warn that they may read the bundles wrong.
+        let verified = GameProfile::named(game.id).unwrap().verified;
+        assert_eq!(
+            String::from_utf8_lossy(&output.stderr).contains("has not been checked"),
+            !verified
+        );
+
+        fs::remove_file(bundle_path).unwrap();
+    }
+}
+
+#[test]
+fn a_game_which_cannot_be_found_needs_an_input() {
+    let steam_path = temp_path("no_steam");
+    let output = Command::new(env!("CARGO_BIN_EXE_bitsquid_re_tools"))
+        .args(["-t", "list", "-g", "wotr", "--steam"])
+        .arg(&steam_path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("wotr is not a game which can be found"));
+}